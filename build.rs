@@ -1,7 +1,17 @@
-use std::process::Command;
+use std::{env, fs, path::PathBuf, process::Command};
+
+#[allow(dead_code)]
+mod cli {
+    include!("src/utils/cli.rs");
+}
+mod cli_assets {
+    include!("src/utils/cli_assets.rs");
+}
 
 fn main() {
     println!("cargo:rerun-if-changed=resources/");
+    println!("cargo:rerun-if-changed=src/utils/cli.rs");
+    println!("cargo:rerun-if-changed=src/utils/cli_assets.rs");
 
     // Ensure that the resources directory exists
     let status = Command::new("glib-compile-resources")
@@ -15,4 +25,35 @@ fn main() {
         panic!("glib-compile-resources failed");
     }
     println!("Resources compiled!!");
+
+    generate_cli_assets();
+}
+
+/// Writes shell completions and the man page into `target/<profile>/assets`
+fn generate_cli_assets() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is not set"));
+    // OUT_DIR is target/<profile>/build/<package>-<hash>/out
+    let assets = out_dir
+        .ancestors()
+        .nth(3)
+        .map_or(out_dir.clone(), |profile| profile.join("assets"));
+    let completions = assets.join("completions");
+    fs::create_dir_all(&completions).expect("Failed to create assets directory");
+
+    let version = env::var("CARGO_PKG_VERSION").unwrap_or_default();
+    let files = [
+        (
+            completions.join("sherlock.bash"),
+            cli_assets::bash_completion(),
+        ),
+        (completions.join("_sherlock"), cli_assets::zsh_completion()),
+        (
+            completions.join("sherlock.fish"),
+            cli_assets::fish_completion(),
+        ),
+        (assets.join("sherlock.1"), cli_assets::man_page(&version)),
+    ];
+    for (path, content) in files {
+        fs::write(&path, content).expect("Failed to write cli assets");
+    }
 }
@@ -6,7 +6,9 @@ mkdir -p ~/.tmp/sherlock-release/
 cargo build --release
 cp target/release/sherlock ~/.tmp/sherlock-release/sherlock
 cp LICENSE ~/.tmp/sherlock-release/LICENSE
+cp -r target/release/assets/completions ~/.tmp/sherlock-release/completions
+cp target/release/assets/sherlock.1 ~/.tmp/sherlock-release/sherlock.1
 
 cd ~/.tmp/sherlock-release/
-tar -czf sherlock-v${version}-bin-linux-x86_64.tar.gz sherlock LICENSE
+tar -czf sherlock-v${version}-bin-linux-x86_64.tar.gz sherlock LICENSE completions sherlock.1
 
@@ -1,34 +1,70 @@
 use std::{
+    collections::HashMap,
     os::unix::process::CommandExt,
     process::{Command, Stdio},
+    sync::Mutex,
 };
+use zbus::{blocking::Connection, zvariant::Value};
 
+use super::systemd::try_move_into_scope;
+use super::util::apply_activation_token;
 use crate::CONFIG;
+use crate::{
+    sherlock_error,
+    utils::errors::{SherlockError, SherlockErrorType},
+};
+
+/// Files or URLs handed to the next launched application. Set by the `OpenWith` api call and
+/// dropped once the window is hidden.
+pub static OPEN_WITH: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Values used to launch a desktop entry's `Exec` key
+/// * **files**: Files or URLs handed to the application (`%f`, `%F`, `%u`, `%U`)
+/// * **icon**: The `Icon` key of the desktop entry (`%i`)
+/// * **name**: The translated `Name` key of the desktop entry (`%c`)
+/// * **desktop_file**: The location of the desktop file (`%k`)
+/// * **working_dir**: The `Path` key of the desktop entry, used as the working directory
+/// * **activation_token**: The startup notification token handed to the application
+#[derive(Debug, Default)]
+pub struct ExecContext<'a> {
+    pub files: &'a [String],
+    pub icon: Option<&'a str>,
+    pub name: Option<&'a str>,
+    pub desktop_file: Option<&'a str>,
+    pub working_dir: Option<&'a str>,
+    pub activation_token: Option<&'a str>,
+}
 
-pub fn applaunch(exec: &str, terminal: bool) -> Option<()> {
-    let config = CONFIG.get()?;
-    let mut parts = Vec::new();
+pub fn applaunch(exec: &str, terminal: bool, context: &ExecContext) -> Result<(), SherlockError> {
+    let config = CONFIG
+        .get()
+        .ok_or(sherlock_error!(SherlockErrorType::ConfigError(None), ""))?;
+    let mut parts: Vec<String> = Vec::new();
 
     if let Some(pre) = &config.behavior.global_prefix {
-        parts.push(pre.to_string());
+        parts.extend(split_command(pre)?);
     }
     if terminal {
-        parts.push(config.default_apps.terminal.clone());
+        parts.extend(split_command(&config.default_apps.terminal)?);
     }
-    parts.push(exec.to_string());
+    parts.extend(parse_exec(&unescape_string(exec), context)?);
     if let Some(flag) = &config.behavior.global_flags {
-        parts.push(flag.to_string());
+        parts.extend(split_command(flag)?);
     }
 
-    let cmd = parts.join(" ").replace('"', "");
-
-    let mut parts = cmd
-        .trim()
-        .split_whitespace()
-        .filter(|s| !s.starts_with("%"));
-
-    let mut command = Command::new(parts.next()?);
+    let mut parts = parts.into_iter();
+    let program = parts.next().ok_or_else(|| {
+        sherlock_error!(
+            SherlockErrorType::ExecParseError(exec.to_string()),
+            "The command line is empty"
+        )
+    })?;
+    let mut command = Command::new(&program);
     command.args(parts);
+    if let Some(dir) = context.working_dir {
+        command.current_dir(dir);
+    }
+    apply_activation_token(&mut command, context.activation_token);
 
     #[cfg(target_family = "unix")]
     unsafe {
@@ -41,8 +77,345 @@ pub fn applaunch(exec: &str, terminal: bool) -> Option<()> {
                 Ok(())
             });
     }
-    let _ = command
-        .spawn()
-        .map_err(|e| eprintln!("Error executing command: {}", e));
-    None
+    let child = command.spawn().map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::CommandExecutionError(exec.to_string()),
+            e.to_string()
+        )
+    })?;
+    try_move_into_scope(&program, child.id());
+    Ok(())
+}
+
+/// Activates a `DBusActivatable` application by calling `org.freedesktop.Application.Activate`
+/// on the bus name matching its desktop file id
+pub fn dbus_activate(app_id: &str, activation_token: Option<&str>) -> Result<(), SherlockError> {
+    let conn = Connection::session()
+        .map_err(|e| sherlock_error!(SherlockErrorType::DBusConnectionError, e.to_string()))?;
+    let path = format!("/{}", app_id.replace('.', "/").replace('-', "_"));
+    let mut platform_data: HashMap<&str, Value> = HashMap::new();
+    if let Some(token) = activation_token {
+        platform_data.insert("activation-token", Value::from(token));
+        platform_data.insert("desktop-startup-id", Value::from(token));
+    }
+    conn.call_method(
+        Some(app_id),
+        path.as_str(),
+        Some("org.freedesktop.Application"),
+        "Activate",
+        &(platform_data,),
+    )
+    .map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::DBusMessageSendError(format!("Activate {}", app_id)),
+            e.to_string()
+        )
+    })?;
+    Ok(())
+}
+
+/// Applies the escape rules for values of type `string` (`\s`, `\n`, `\t`, `\r` and `\\`).
+/// Any other backslash sequence is kept as is, so it can be handled by the quoting rules.
+pub fn unescape_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Splits an `Exec` value into its arguments and expands its field codes according to the
+/// desktop entry specification.
+///
+/// Field codes inside quoted arguments are left untouched, as their expansion is undefined by
+/// the specification. Deprecated (`%d`, `%D`, `%n`, `%N`, `%v`, `%m`) and unknown field codes
+/// are removed.
+pub fn parse_exec(exec: &str, context: &ExecContext) -> Result<Vec<String>, SherlockError> {
+    let mut args = Vec::new();
+    for arg in split_exec(exec)? {
+        match arg.as_slice() {
+            [Piece::Field(code)] => match code {
+                'f' | 'u' => args.extend(context.files.first().cloned()),
+                'F' | 'U' => args.extend(context.files.iter().cloned()),
+                'i' => {
+                    if let Some(icon) = context.icon.filter(|icon| !icon.is_empty()) {
+                        args.push(String::from("--icon"));
+                        args.push(icon.to_string());
+                    }
+                }
+                'c' => args.extend(context.name.map(String::from)),
+                'k' => args.extend(context.desktop_file.map(String::from)),
+                _ => {}
+            },
+            pieces => {
+                let mut value = String::new();
+                for piece in pieces {
+                    match piece {
+                        Piece::Literal(literal) => value.push_str(literal),
+                        Piece::Field(code) => value.push_str(inline_field(*code, context)),
+                    }
+                }
+                args.push(value);
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// Splits a configured command line, e.g. the terminal or the global prefix, using the quoting
+/// rules of `Exec` values. Field codes have no meaning there and are kept as they are.
+fn split_command(value: &str) -> Result<Vec<String>, SherlockError> {
+    let args = split_exec(value)?
+        .into_iter()
+        .map(|pieces| {
+            pieces
+                .into_iter()
+                .map(|piece| match piece {
+                    Piece::Literal(literal) => literal,
+                    Piece::Field(code) => format!("%{}", code),
+                })
+                .collect()
+        })
+        .collect();
+    Ok(args)
+}
+
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Literal(String),
+    Field(char),
+}
+
+fn push_literal(arg: &mut Vec<Piece>, c: char) {
+    match arg.last_mut() {
+        Some(Piece::Literal(literal)) => literal.push(c),
+        _ => arg.push(Piece::Literal(c.to_string())),
+    }
+}
+
+/// Value of a field code that is part of a larger argument, i.e. `--file=%f`
+fn inline_field<'a>(code: char, context: &ExecContext<'a>) -> &'a str {
+    match code {
+        'f' | 'u' | 'F' | 'U' => context.files.first().map_or("", |f| f.as_str()),
+        'i' => context.icon.unwrap_or(""),
+        'c' => context.name.unwrap_or(""),
+        'k' => context.desktop_file.unwrap_or(""),
+        _ => "",
+    }
+}
+
+fn split_exec(exec: &str) -> Result<Vec<Vec<Piece>>, SherlockError> {
+    let unterminated = || {
+        sherlock_error!(
+            SherlockErrorType::ExecParseError(exec.to_string()),
+            "Unterminated quoted argument"
+        )
+    };
+    let mut args: Vec<Vec<Piece>> = Vec::new();
+    let mut current: Option<Vec<Piece>> = None;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(Vec::new);
+                loop {
+                    match chars.next().ok_or_else(unterminated)? {
+                        '"' => break,
+                        '\\' => match chars.next().ok_or_else(unterminated)? {
+                            escaped @ ('"' | '`' | '$' | '\\') => push_literal(arg, escaped),
+                            other => {
+                                push_literal(arg, '\\');
+                                push_literal(arg, other);
+                            }
+                        },
+                        '%' => match chars.next().ok_or_else(unterminated)? {
+                            '%' => push_literal(arg, '%'),
+                            '"' => {
+                                push_literal(arg, '%');
+                                break;
+                            }
+                            other => {
+                                push_literal(arg, '%');
+                                push_literal(arg, other);
+                            }
+                        },
+                        other => push_literal(arg, other),
+                    }
+                }
+            }
+            '\\' => {
+                let arg = current.get_or_insert_with(Vec::new);
+                push_literal(arg, chars.next().unwrap_or('\\'));
+            }
+            '%' => {
+                let arg = current.get_or_insert_with(Vec::new);
+                match chars.next() {
+                    Some('%') | None => push_literal(arg, '%'),
+                    Some(code) => arg.push(Piece::Field(code)),
+                }
+            }
+            other => push_literal(current.get_or_insert_with(Vec::new), other),
+        }
+    }
+    if let Some(arg) = current {
+        args.push(arg);
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(exec: &str) -> Vec<String> {
+        parse_exec(exec, &ExecContext::default()).unwrap()
+    }
+
+    #[test]
+    fn splits_plain_arguments() {
+        assert_eq!(
+            parse("firefox --new-window"),
+            vec!["firefox", "--new-window"]
+        );
+        assert_eq!(parse("  foo   bar "), vec!["foo", "bar"]);
+    }
+
+    #[test]
+    fn keeps_spaces_in_quoted_arguments() {
+        assert_eq!(
+            parse(r#""/opt/My App/bin/app" --flag"#),
+            vec!["/opt/My App/bin/app", "--flag"]
+        );
+        assert_eq!(parse(r#"app --name="a b""#), vec!["app", "--name=a b"]);
+        assert_eq!(parse(r#"app """#), vec!["app", ""]);
+    }
+
+    #[test]
+    fn unescapes_reserved_characters_in_quotes() {
+        assert_eq!(
+            parse(r#"sh -c "echo \"\$HOME\" \`date\`""#),
+            vec!["sh", "-c", r#"echo "$HOME" `date`"#]
+        );
+    }
+
+    #[test]
+    fn applies_string_escapes_before_quoting() {
+        // A literal backslash in a quoted argument takes four backslashes in the file
+        let exec = unescape_string(r#"app "C:\\\\Temp""#);
+        assert_eq!(parse(&exec), vec!["app", r"C:\Temp"]);
+        assert_eq!(unescape_string(r"a\sb\tc"), "a b\tc");
+    }
+
+    #[test]
+    fn expands_literal_percent() {
+        assert_eq!(parse("printf 100%%"), vec!["printf", "100%"]);
+        assert_eq!(parse(r#"printf "100%%""#), vec!["printf", "100%"]);
+    }
+
+    #[test]
+    fn expands_file_and_url_codes() {
+        let files = vec![String::from("/tmp/a b.txt"), String::from("/tmp/c.txt")];
+        let context = ExecContext {
+            files: &files,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_exec("editor %f", &context).unwrap(),
+            vec!["editor", "/tmp/a b.txt"]
+        );
+        assert_eq!(
+            parse_exec("editor %F", &context).unwrap(),
+            vec!["editor", "/tmp/a b.txt", "/tmp/c.txt"]
+        );
+        assert_eq!(
+            parse_exec("browser %U --private", &context).unwrap(),
+            vec!["browser", "/tmp/a b.txt", "/tmp/c.txt", "--private"]
+        );
+        assert_eq!(
+            parse_exec("editor --open=%u", &context).unwrap(),
+            vec!["editor", "--open=/tmp/a b.txt"]
+        );
+    }
+
+    #[test]
+    fn removes_empty_file_codes() {
+        assert_eq!(parse("editor %f"), vec!["editor"]);
+        assert_eq!(parse("browser %U --private"), vec!["browser", "--private"]);
+    }
+
+    #[test]
+    fn expands_icon_name_and_location() {
+        let context = ExecContext {
+            icon: Some("firefox"),
+            name: Some("Firefox Web Browser"),
+            desktop_file: Some("/usr/share/applications/firefox.desktop"),
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_exec("firefox %i --class %c %k", &context).unwrap(),
+            vec![
+                "firefox",
+                "--icon",
+                "firefox",
+                "--class",
+                "Firefox Web Browser",
+                "/usr/share/applications/firefox.desktop",
+            ]
+        );
+        assert_eq!(parse("firefox %i %c %k"), vec!["firefox"]);
+    }
+
+    #[test]
+    fn removes_deprecated_codes() {
+        assert_eq!(parse("app %d %D %n %N %v %m"), vec!["app"]);
+    }
+
+    #[test]
+    fn leaves_codes_in_quotes_untouched() {
+        assert_eq!(parse(r#"sh -c "echo %f""#), vec!["sh", "-c", "echo %f"]);
+    }
+
+    #[test]
+    fn splits_configured_commands() {
+        assert_eq!(
+            split_command(r#"env "MY VAR=a b" uwsm app --"#).unwrap(),
+            vec!["env", "MY VAR=a b", "uwsm", "app", "--"]
+        );
+        assert_eq!(
+            split_command(r#""/opt/My Terminal/term" -e"#).unwrap(),
+            vec!["/opt/My Terminal/term", "-e"]
+        );
+        assert_eq!(
+            split_command("app --title=%c 100%%").unwrap(),
+            vec!["app", "--title=%c", "100%"]
+        );
+        assert!(split_command(r#"term "-e"#).is_err());
+    }
+
+    #[test]
+    fn rejects_unterminated_quotes() {
+        assert!(parse_exec(r#"app "unterminated"#, &ExecContext::default()).is_err());
+    }
 }
@@ -1,11 +1,17 @@
 use std::process::{Command, Stdio};
 
+use super::systemd::try_move_into_scope;
+use super::util::apply_activation_token;
 use crate::{sher_log, CONFIG};
 use crate::{
     sherlock_error,
     utils::errors::{SherlockError, SherlockErrorType},
 };
-pub fn command_launch(exec: &str, keyword: &str) -> Result<(), SherlockError> {
+pub fn command_launch(
+    exec: &str,
+    keyword: &str,
+    activation_token: Option<&str>,
+) -> Result<(), SherlockError> {
     let config = CONFIG
         .get()
         .ok_or(sherlock_error!(SherlockErrorType::ConfigError(None), ""))?;
@@ -24,17 +30,23 @@ pub fn command_launch(exec: &str, keyword: &str) -> Result<(), SherlockError> {
     let commands = exec.split("&").map(|s| s.trim()).filter(|s| !s.is_empty());
 
     for command in commands {
-        asynchronous_execution(command, &prefix, &flags)?;
+        asynchronous_execution(command, &prefix, &flags, activation_token)?;
     }
     Ok(())
 }
 
-pub fn asynchronous_execution(cmd: &str, prefix: &str, flags: &str) -> Result<(), SherlockError> {
+pub fn asynchronous_execution(
+    cmd: &str,
+    prefix: &str,
+    flags: &str,
+    activation_token: Option<&str>,
+) -> Result<(), SherlockError> {
     let raw_command = format!("{}{}{}", prefix, cmd, flags).replace(r#"\""#, "'");
     sher_log!(format!(r#"Spawning command "{}""#, raw_command));
 
     let mut command = Command::new("sh");
     command.arg("-c").arg(raw_command.clone());
+    apply_activation_token(&mut command, activation_token);
 
     command
         .stdin(Stdio::piped())
@@ -53,6 +65,11 @@ pub fn asynchronous_execution(cmd: &str, prefix: &str, flags: &str) -> Result<()
         )
     })?;
 
+    // The scope holds the `sh -c` wrapper together with the processes it starts. It is named
+    // after the first word of the command.
+    let program = cmd.split_whitespace().next().unwrap_or("sh");
+    try_move_into_scope(program, child.id());
+
     tokio::spawn(async move {
         let result = match child.wait_with_output() {
             Ok(output) => {
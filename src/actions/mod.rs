@@ -1,3 +1,4 @@
+use applaunch::{ExecContext, OPEN_WITH};
 use gio::glib::{object::IsA, variant::ToVariant};
 use gtk4::{prelude::WidgetExt, Widget};
 use std::collections::HashMap;
@@ -6,19 +7,22 @@ use teamslaunch::teamslaunch;
 use util::{clear_cached_files, reset_app_counter};
 
 use crate::{
-    daemon::daemon::print_reponse,
+    api::events::SherlockEvent,
+    daemon::daemon::{print_reponse, EXIT_CODE},
     launcher::{
         audio_launcher::MusicPlayerLauncher, process_launcher::ProcessLauncher,
         theme_picker::ThemePicker,
     },
-    loader::util::CounterReader,
+    loader::util::{CounterReader, SelectionReader},
     sherlock_error,
+    ui::window::activation_token,
     utils::{errors::SherlockErrorType, files::home_dir},
     CONFIG,
 };
 
 pub mod applaunch;
 pub mod commandlaunch;
+pub mod systemd;
 pub mod teamslaunch;
 pub mod util;
 pub mod websearch;
@@ -33,6 +37,7 @@ pub fn execute_from_attrs<T: IsA<Widget>>(
         .into_iter()
         .map(|(k, v)| (k.to_string(), v.to_string()))
         .collect();
+    SherlockEvent::row_activated(&attrs).emit();
 
     if let Some(method) = attrs.get("method") {
         let mut exit = do_exit.unwrap_or(attrs.get("exit").map_or(true, |s| s == "true"));
@@ -48,8 +53,32 @@ pub fn execute_from_attrs<T: IsA<Widget>>(
             "app_launcher" => {
                 let exec = attrs.get("exec").map_or("", |s| s.as_str());
                 let term = attrs.get("term").map_or(false, |s| s.as_str() == "true");
-                applaunch::applaunch(exec, term);
-                increment(&exec);
+                let token = activation_token(row);
+                let files = OPEN_WITH
+                    .lock()
+                    .map(|mut files| std::mem::take(&mut *files))
+                    .unwrap_or_default();
+                let context = ExecContext {
+                    files: &files,
+                    icon: attrs.get("icon").map(String::as_str),
+                    name: attrs.get("name").map(String::as_str),
+                    desktop_file: attrs.get("desktop_file").map(String::as_str),
+                    working_dir: attrs.get("working_dir").map(String::as_str),
+                    activation_token: token.as_deref(),
+                };
+                // Fall back to Exec if the application cannot be activated over D-Bus. Files
+                // are only handed over through Exec.
+                let result = match attrs.get("dbus_app_id").filter(|_| files.is_empty()) {
+                    Some(app_id) => applaunch::dbus_activate(app_id, token.as_deref())
+                        .or_else(|_| applaunch::applaunch(exec, term, &context)),
+                    None => applaunch::applaunch(exec, term, &context),
+                };
+                if let Err(error) = result {
+                    exit = false;
+                    let _result = error.insert(false);
+                } else {
+                    increment(&exec, attrs.get("keyword").map(String::as_str));
+                }
             }
             "web_launcher" | "bookmarks" => {
                 let engine = attrs.get("engine").map_or("plain", |s| s.as_str());
@@ -57,12 +86,13 @@ pub fn execute_from_attrs<T: IsA<Widget>>(
                     query.as_str()
                 } else if let Some(query) = attrs.get("keyword") {
                     let exec = format!("websearch-{}", engine);
-                    increment(&exec);
+                    increment(&exec, Some(query.as_str()));
                     query.as_str()
                 } else {
                     ""
                 };
-                if let Err(error) = websearch::websearch(engine, query) {
+                let token = activation_token(row);
+                if let Err(error) = websearch::websearch(engine, query, token.as_deref()) {
                     exit = false;
                     let _result = error.insert(false);
                 }
@@ -70,11 +100,12 @@ pub fn execute_from_attrs<T: IsA<Widget>>(
             "command" => {
                 let exec = attrs.get("exec").map_or("", |s| s.as_str());
                 let keyword = attrs.get("keyword").map_or("", |s| s.as_str());
-                if let Err(error) = commandlaunch::command_launch(exec, keyword) {
+                let token = activation_token(row);
+                if let Err(error) = commandlaunch::command_launch(exec, keyword, token.as_deref()) {
                     exit = false;
                     let _result = error.insert(false);
                 } else {
-                    increment(&exec);
+                    increment(&exec, Some(keyword));
                 }
             }
             "copy" => {
@@ -103,7 +134,8 @@ pub fn execute_from_attrs<T: IsA<Widget>>(
             }
             "teams_event" => {
                 if let Some(meeting) = attrs.get("meeting_url") {
-                    match teamslaunch(meeting) {
+                    let token = activation_token(row);
+                    match teamslaunch(meeting, token.as_deref()) {
                         Ok(_) => {
                             let _ = row.activate_action("win.close", None);
                         }
@@ -171,21 +203,21 @@ pub fn execute_from_attrs<T: IsA<Widget>>(
                             "win.switch-page",
                             Some(&String::from("search-page->error-page").to_variant()),
                         ) {
-                            increment("debug.show_errors");
+                            increment("debug.show_errors", None);
                         }
                     }
                     "clear_cache" => {
                         if let Err(error) = clear_cached_files() {
                             let _result = error.insert(false);
                         } else {
-                            increment("debug.clear_cache");
+                            increment("debug.clear_cache", None);
                         }
                     }
                     "reset_counts" => {
                         if let Err(error) = reset_app_counter() {
                             let _result = error.insert(false);
                         } else {
-                            increment("debug.reset_counts");
+                            increment("debug.reset_counts", None);
                         }
                     }
                     "reset_log" => {
@@ -230,6 +262,7 @@ pub fn execute_from_attrs<T: IsA<Widget>>(
 
         exit = do_exit.unwrap_or(exit);
         if exit {
+            EXIT_CODE.commit();
             eval_close(row);
         }
     }
@@ -246,10 +279,13 @@ pub fn get_attrs_map(in_attrs: Vec<(&str, Option<&str>)>) -> HashMap<String, Str
         })
         .collect()
 }
-fn increment(key: &str) {
+fn increment(key: &str, query: Option<&str>) {
     if let Ok(count_reader) = CounterReader::new() {
         let _ = count_reader.increment(key);
     };
+    if let (Some(query), Ok(selection_reader)) = (query, SelectionReader::new()) {
+        let _ = selection_reader.record(query, key);
+    }
 }
 fn eval_close<T: IsA<Widget>>(row: &T) {
     let _ = row.activate_action("win.close", None);
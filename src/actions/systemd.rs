@@ -0,0 +1,177 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use zbus::{
+    blocking::Connection,
+    zvariant::{OwnedObjectPath, Value},
+};
+
+use crate::{
+    sher_log, sherlock_error,
+    utils::errors::{SherlockError, SherlockErrorType},
+    CONFIG,
+};
+
+/// Whether launched processes should be placed into their own transient systemd scope
+pub fn use_systemd_scope() -> bool {
+    CONFIG.get().map_or(false, |config| {
+        config.behavior.launch_backend == "systemd-scope"
+    })
+}
+
+/// Moves a freshly spawned process into its own scope if the `systemd-scope` backend is used.
+/// The process was already launched, so failures (e.g. no systemd user bus) are only logged and
+/// the process keeps running in Sherlock's cgroup.
+pub fn try_move_into_scope(program: &str, pid: u32) {
+    if !use_systemd_scope() {
+        return;
+    }
+    if let Err(error) = move_into_scope(program, pid) {
+        sher_log!(format!(
+            "Failed to move {} ({}) into a systemd scope: {}",
+            program, pid, error
+        ));
+    }
+}
+
+/// Moves a freshly spawned process into its own transient user scope, so it no longer lives in
+/// Sherlock's cgroup and survives restarts of the daemon.
+pub fn move_into_scope(program: &str, pid: u32) -> Result<(), SherlockError> {
+    let conn = Connection::session()
+        .map_err(|e| sherlock_error!(SherlockErrorType::DBusConnectionError, e.to_string()))?;
+    start_transient_scope(&conn, &scope_name(program, pid), pid)?;
+    Ok(())
+}
+
+/// Calls `StartTransientUnit` on the systemd manager reachable through `conn` to create the
+/// scope `unit` containing `pid`. Returns the object path of the queued job.
+pub fn start_transient_scope(
+    conn: &Connection,
+    unit: &str,
+    pid: u32,
+) -> Result<OwnedObjectPath, SherlockError> {
+    let properties: Vec<(&str, Value)> = vec![
+        ("PIDs", Value::from(vec![pid])),
+        ("CollectMode", Value::from("inactive-or-failed")),
+        (
+            "Description",
+            Value::from("Application launched by Sherlock"),
+        ),
+    ];
+    let aux: Vec<(&str, Vec<(&str, Value)>)> = Vec::new();
+    let reply = conn
+        .call_method(
+            Some("org.freedesktop.systemd1"),
+            "/org/freedesktop/systemd1",
+            Some("org.freedesktop.systemd1.Manager"),
+            "StartTransientUnit",
+            &(unit, "fail", properties, aux),
+        )
+        .map_err(|e| {
+            sherlock_error!(
+                SherlockErrorType::DBusMessageSendError(format!("StartTransientUnit {}", unit)),
+                e.to_string()
+            )
+        })?;
+    reply.body().deserialize().map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::DBusMessageSendError(format!("StartTransientUnit {}", unit)),
+            e.to_string()
+        )
+    })
+}
+
+/// Builds a unit name of the form `app-sherlock-<name>-<rand>.scope`. Characters that are
+/// not allowed in unit names are replaced by `_`.
+pub fn scope_name(program: &str, pid: u32) -> String {
+    let name: String = Path::new(program)
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '.' => c,
+            _ => '_',
+        })
+        .collect();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    let rand = RandomState::new().hash_one((pid, nanos)) as u32;
+    format!("app-sherlock-{}-{:08x}.scope", name, rand)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_bus::TestBus;
+    use std::sync::{Arc, Mutex};
+    use zbus::zvariant::OwnedValue;
+
+    #[derive(Clone, Default)]
+    struct MockManager {
+        units: Arc<Mutex<Vec<(String, String, Vec<u32>)>>>,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.systemd1.Manager")]
+    impl MockManager {
+        fn start_transient_unit(
+            &self,
+            name: String,
+            mode: String,
+            properties: Vec<(String, OwnedValue)>,
+            _aux: Vec<(String, Vec<(String, OwnedValue)>)>,
+        ) -> OwnedObjectPath {
+            let pids = properties
+                .iter()
+                .find(|(key, _)| key == "PIDs")
+                .and_then(|(_, value)| Vec::<u32>::try_from(value.try_clone().ok()?).ok())
+                .unwrap_or_default();
+            self.units.lock().unwrap().push((name, mode, pids));
+            OwnedObjectPath::try_from("/org/freedesktop/systemd1/job/1").unwrap()
+        }
+    }
+
+    #[test]
+    fn scope_names_are_valid_units() {
+        let name = scope_name("/usr/bin/my app+", 42);
+        assert!(name.starts_with("app-sherlock-my_app_-"));
+        assert!(name.ends_with(".scope"));
+        assert_ne!(name, scope_name("/usr/bin/my app+", 42));
+    }
+
+    #[test]
+    fn starts_transient_scope() {
+        let Some(bus) = TestBus::start() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+        let manager = MockManager::default();
+        let _service = zbus::blocking::connection::Builder::address(bus.address())
+            .unwrap()
+            .name("org.freedesktop.systemd1")
+            .unwrap()
+            .serve_at("/org/freedesktop/systemd1", manager.clone())
+            .unwrap()
+            .build()
+            .unwrap();
+        let client = zbus::blocking::connection::Builder::address(bus.address())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let job = start_transient_scope(&client, "app-sherlock-test-0.scope", 1234).unwrap();
+        assert_eq!(job.as_str(), "/org/freedesktop/systemd1/job/1");
+        assert_eq!(
+            *manager.units.lock().unwrap(),
+            vec![(
+                String::from("app-sherlock-test-0.scope"),
+                String::from("fail"),
+                vec![1234]
+            )]
+        );
+    }
+}
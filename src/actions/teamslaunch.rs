@@ -3,13 +3,14 @@ use std::{
     process::{Command, Stdio},
 };
 
+use super::util::apply_activation_token;
 use crate::CONFIG;
 use crate::{
     sherlock_error,
     utils::errors::{SherlockError, SherlockErrorType},
 };
 
-pub fn teamslaunch(meeting_url: &str) -> Result<(), SherlockError> {
+pub fn teamslaunch(meeting_url: &str, activation_token: Option<&str>) -> Result<(), SherlockError> {
     if let Some(c) = CONFIG.get() {
         let teams_command = c.default_apps.teams.clone();
         let exec = teams_command.replace("{meeting_url}", meeting_url);
@@ -30,6 +31,8 @@ pub fn teamslaunch(meeting_url: &str) -> Result<(), SherlockError> {
             }
         }
 
+        apply_activation_token(&mut command, activation_token);
+
         #[cfg(target_family = "unix")]
         unsafe {
             command
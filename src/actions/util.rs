@@ -3,7 +3,10 @@ use std::{fs, process::Command};
 use cli_clipboard::{ClipboardContext, ClipboardProvider};
 
 use crate::{
-    loader::application_loader::{get_applications_dir, get_desktop_files},
+    loader::{
+        application_loader::{get_applications_dir, get_desktop_files},
+        util::SelectionReader,
+    },
     utils::{
         errors::{SherlockError, SherlockErrorType},
         files::{home_dir, read_lines},
@@ -49,8 +52,19 @@ pub fn clear_cached_files() -> Result<(), SherlockError> {
     Ok(())
 }
 
+/// Exports the activation token to the launched process, for Wayland (`XDG_ACTIVATION_TOKEN`)
+/// as well as X11 (`DESKTOP_STARTUP_ID`) applications
+pub fn apply_activation_token(command: &mut Command, token: Option<&str>) {
+    if let Some(token) = token {
+        command
+            .env("XDG_ACTIVATION_TOKEN", token)
+            .env("DESKTOP_STARTUP_ID", token);
+    }
+}
+
 pub fn reset_app_counter() -> Result<(), SherlockError> {
     let home = home_dir()?;
+    SelectionReader::new()?.reset()?;
     fs::remove_file(home.join(".sherlock/counts.json")).map_err(|e| {
         sherlock_error!(
             SherlockErrorType::FileRemoveError(home.join(".sherlock/counts.json")),
@@ -4,7 +4,11 @@ use super::commandlaunch::command_launch;
 use super::util::parse_default_browser;
 use crate::utils::errors::SherlockError;
 
-pub fn websearch(engine: &str, query: &str) -> Result<(), SherlockError> {
+pub fn websearch(
+    engine: &str,
+    query: &str,
+    activation_token: Option<&str>,
+) -> Result<(), SherlockError> {
     let engines: HashMap<&str, &str> = HashMap::from([
         ("google", "https://www.google.com/search?q={keyword}"),
         ("bing", "https://www.bing.com/search?q={keyword}"),
@@ -36,5 +40,5 @@ pub fn websearch(engine: &str, query: &str) -> Result<(), SherlockError> {
         browser.push_str(&format!(" {}", url));
         browser
     };
-    command_launch(&command, "")
+    command_launch(&command, "", activation_token)
 }
@@ -1,25 +1,33 @@
-use std::{fmt::Display, sync::RwLock};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    sync::RwLock,
+};
 
 use gdk_pixbuf::subclass::prelude::ObjectSubclassIsExt;
 use gio::{
     glib::{object::ObjectExt, variant::ToVariant, WeakRef},
+    prelude::ListModelExt,
     ListStore,
 };
 use gtk4::{
-    prelude::{EntryExt, GtkWindowExt, WidgetExt},
-    Application, ApplicationWindow, Stack,
+    prelude::{CastNone, EditableExt, EntryExt, GtkWindowExt, WidgetExt},
+    Application, ApplicationWindow, ListScrollFlags, SingleSelection, Stack,
 };
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use simd_json::prelude::ArrayTrait;
 
 use crate::{
-    actions::{execute_from_attrs, get_attrs_map},
+    actions::{applaunch::OPEN_WITH, execute_from_attrs, get_attrs_map},
+    g_subclasses::sherlock_row::SherlockRow,
     loader::{
         pipe_loader::{PipedData, PipedElements},
         util::JsonCache,
+        Loader,
     },
-    prelude::StackHelpers,
+    prelude::{SherlockNav, StackHelpers},
     sher_log,
     ui::{
         input_window::InputWindow,
@@ -27,11 +35,17 @@ use crate::{
         tiles::Tile,
         util::{display_raw, SearchHandler, SherlockAction, SherlockCounter},
     },
-    utils::errors::SherlockError,
+    utils::{
+        config::{PipeBind, SherlockConfig, SherlockFlags},
+        errors::SherlockError,
+    },
     CONFIG,
 };
 
-use super::call::ApiCall;
+use super::{
+    call::{ApiCall, ApiRequest, ApiResponse, RowInfo},
+    events::SherlockEvent,
+};
 
 pub static RESPONSE_SOCKET: Lazy<RwLock<Option<String>>> = Lazy::new(|| RwLock::new(None));
 
@@ -43,7 +57,10 @@ pub struct SherlockAPI {
     pub search_ui: Option<WeakRef<SearchUiObj>>,
     pub search_handler: Option<SearchHandler>,
     pub errors: Option<WeakRef<ListStore>>,
+    pub flags: Option<SherlockFlags>,
     pub queue: Vec<ApiCall>,
+    /// Number of lines received through the current pipe
+    pub pipe_lines: usize,
 }
 impl SherlockAPI {
     pub fn new(app: &Application) -> Self {
@@ -55,7 +72,9 @@ impl SherlockAPI {
             search_ui: None,
             search_handler: None,
             errors: None,
+            flags: None,
             queue: vec![],
+            pipe_lines: 0,
         }
     }
 
@@ -83,10 +102,31 @@ impl SherlockAPI {
         self.queue.push(request);
         Some(())
     }
+    /// Handles an enveloped call right away and reports the outcome. Unlike bare calls, calls
+    /// that cannot be handled yet, e.g. because the window has not been built, are not queued.
+    pub fn handle_request(&mut self, request: ApiRequest) -> ApiResponse {
+        self.flush();
+        let ApiRequest { id, call } = request;
+        if call.is_query() {
+            return match self.match_query(&call) {
+                Some(result) => ApiResponse::success(id, Some(result)),
+                None => ApiResponse::failure(id, format!("{} is not available yet", call)),
+            };
+        }
+        if let ApiCall::Subscribe(_) = call {
+            return ApiResponse::failure(id, format!("{} needs a daemon connection", call));
+        }
+        if self.match_action(&call).is_some() {
+            return ApiResponse::success(id, None);
+        }
+        sher_log!(format!("Action {} could not be handled", call));
+        ApiResponse::failure(id, format!("{} could not be handled yet", call))
+    }
 
     pub fn match_action(&mut self, api_call: &ApiCall) -> Option<()> {
         match api_call {
             ApiCall::Obfuscate(vis) => self.obfuscate(*vis),
+            ApiCall::Bind(bind) => self.add_bind(bind),
             ApiCall::Clear => self.clear_results(),
             ApiCall::SherlockError(err) => self.insert_msg(err, true),
             ApiCall::SherlockWarning(err) => self.insert_msg(err, false),
@@ -94,11 +134,106 @@ impl SherlockAPI {
             ApiCall::Show => self.open(),
             ApiCall::ClearAwaiting => self.flush(),
             ApiCall::Pipe(pipe) => self.load_pipe_elements(pipe),
+            ApiCall::AppendPipe(pipe) => self.append_pipe_elements(pipe),
+            ApiCall::PipeOpen(open) => self.set_spinner(*open),
             ApiCall::DisplayRaw(pipe) => self.display_raw(pipe),
             ApiCall::SwitchMode(mode) => self.switch_mode(mode),
             ApiCall::Socket(socket) => self.create_socket(socket.as_deref()),
+            ApiCall::SetQuery(query) => self.set_query(query),
+            ApiCall::SelectIndex(index) => self.select_index(*index),
+            ApiCall::ActivateSelected => self.activate_selected(),
+            ApiCall::Close => self.close(),
+            ApiCall::Reload => self.reload(),
+            ApiCall::OpenWith(files) => self.open_with(files),
+            // Queries have no effect without a reply channel
+            ApiCall::GetMode
+            | ApiCall::GetQuery
+            | ApiCall::ListModes
+            | ApiCall::ListRows
+            | ApiCall::GetSelected => Some(()),
+            // Subscriptions are handled by the daemon connection itself
+            ApiCall::Subscribe(_) => Some(()),
+        }
+    }
+    /// Answers read-only calls. Returns `None` if the call is no query or if the requested
+    /// state does not exist yet.
+    pub fn match_query(&self, api_call: &ApiCall) -> Option<Value> {
+        match api_call {
+            ApiCall::GetMode => self.get_mode(),
+            ApiCall::GetQuery => self.get_query(),
+            ApiCall::ListModes => self.list_modes(),
+            ApiCall::ListRows => self.list_rows(),
+            ApiCall::GetSelected => self.get_selected(),
+            _ => None,
         }
     }
+    fn get_mode(&self) -> Option<Value> {
+        let handler = self.search_handler.as_ref()?;
+        let ui = self.search_ui.as_ref().and_then(|ui| ui.upgrade())?;
+        let mode = handler.mode.borrow().trim().to_string();
+        let title = ui.imp().mode_title.text().to_string();
+        Some(json!({ "mode": mode, "title": title }))
+    }
+    fn get_query(&self) -> Option<Value> {
+        let ui = self.search_ui.as_ref().and_then(|ui| ui.upgrade())?;
+        Some(Value::String(ui.imp().search_bar.text().to_string()))
+    }
+    fn list_modes(&self) -> Option<Value> {
+        let handler = self.search_handler.as_ref()?;
+        let modes: BTreeMap<String, String> = handler
+            .modes
+            .borrow()
+            .iter()
+            .map(|(alias, name)| (alias.trim().to_string(), name.clone().unwrap_or_default()))
+            .collect();
+        serde_json::to_value(modes).ok()
+    }
+    /// Lists the displayed rows in display order, followed by the hidden ones. Visibility is
+    /// read from the displayed model so that the filter does not run again.
+    fn list_rows(&self) -> Option<Value> {
+        let ui = self.search_ui.as_ref().and_then(|ui| ui.upgrade())?;
+        let handler = self.search_handler.as_ref()?;
+        let model = handler.model.as_ref().and_then(|m| m.upgrade())?;
+        let selection = ui.imp().results.model().and_downcast::<SingleSelection>()?;
+        let displayed: Vec<SherlockRow> = (0..selection.n_items())
+            .filter_map(|i| selection.item(i).and_downcast::<SherlockRow>())
+            .collect();
+        let shown: HashSet<&SherlockRow> = displayed.iter().collect();
+        let hidden: Vec<SherlockRow> = (0..model.n_items())
+            .filter_map(|i| model.item(i).and_downcast::<SherlockRow>())
+            .filter(|row| !shown.contains(row))
+            .collect();
+        let info = |row: &SherlockRow, visible: bool| RowInfo {
+            title: row.title(),
+            launcher: row.launcher(),
+            priority: row.priority(),
+            visible,
+        };
+        let rows: Vec<RowInfo> = displayed
+            .iter()
+            .map(|row| info(row, true))
+            .chain(hidden.iter().map(|row| info(row, false)))
+            .collect();
+        serde_json::to_value(rows).ok()
+    }
+    fn get_selected(&self) -> Option<Value> {
+        let ui = self.search_ui.as_ref().and_then(|ui| ui.upgrade())?;
+        let results = &ui.imp().results;
+        let Some(row) = results.selected_item().and_downcast::<SherlockRow>() else {
+            return Some(Value::Null);
+        };
+        let index = results
+            .model()
+            .and_downcast::<SingleSelection>()?
+            .selected();
+        let info = RowInfo {
+            title: row.title(),
+            launcher: row.launcher(),
+            priority: row.priority(),
+            visible: true,
+        };
+        Some(json!({ "index": index, "row": info }))
+    }
     pub fn open(&self) -> Option<()> {
         let window = self.window.as_ref().and_then(|win| win.upgrade())?;
         let open_window = self.open_window.as_ref().and_then(|win| win.upgrade())?;
@@ -126,6 +261,77 @@ impl SherlockAPI {
         open_window.present();
         Some(())
     }
+    /// Shows the window. The application launched next opens `files`.
+    pub fn open_with(&self, files: &[String]) -> Option<()> {
+        *OPEN_WITH.lock().ok()? = files.to_vec();
+        self.open()
+    }
+    pub fn close(&self) -> Option<()> {
+        let window = self.window.as_ref().and_then(|win| win.upgrade())?;
+        window.activate_action("win.close", None).ok()
+    }
+    /// Types `query` into the search bar. A leading mode alias (e.g. `pm `) switches into that
+    /// mode first, just like typing it would.
+    pub fn set_query(&self, query: &str) -> Option<()> {
+        let ui = self.search_ui.as_ref().and_then(|ui| ui.upgrade())?;
+        let handler = self.search_handler.as_ref()?;
+        let query = {
+            let modes = handler.modes.borrow();
+            let (alias, query) = split_mode_alias(&modes, query);
+            if let Some(alias) = alias {
+                let _ = ui.activate_action("win.switch-mode", Some(&alias.to_variant()));
+            }
+            query
+        };
+        let search_bar = &ui.imp().search_bar;
+        search_bar.set_text(query);
+        search_bar.set_position(-1);
+        Some(())
+    }
+    /// Selects the row at `index` among the visible rows. Indices past the end select the last
+    /// row.
+    pub fn select_index(&self, index: u32) -> Option<()> {
+        let ui = self.search_ui.as_ref().and_then(|ui| ui.upgrade())?;
+        let results = &ui.imp().results;
+        let selection = results.model().and_downcast::<SingleSelection>()?;
+        let index = clamp_index(index, selection.n_items())?;
+        selection.set_selected(index);
+        results.scroll_to(index, ListScrollFlags::NONE, None);
+        Some(())
+    }
+    pub fn activate_selected(&self) -> Option<()> {
+        let ui = self.search_ui.as_ref().and_then(|ui| ui.upgrade())?;
+        let row = ui
+            .imp()
+            .results
+            .selected_item()
+            .and_downcast::<SherlockRow>()?;
+        let exit: u8 = 0;
+        row.emit_by_name::<()>("row-should-activate", &[&exit, &"api"]);
+        Some(())
+    }
+    /// Reads the configuration and style sheet again and repopulates the launchers. Errors
+    /// are shown in the error view instead of keeping the call queued.
+    pub fn reload(&self) -> Option<()> {
+        let handler = self.search_handler.as_ref()?;
+        let flags = self.flags.clone().unwrap_or_default();
+        match SherlockConfig::from_flags(&flags) {
+            Ok((config, warnings)) => {
+                CONFIG.replace(config);
+                warnings.iter().for_each(|warning| {
+                    self.insert_msg(warning, false);
+                });
+            }
+            Err(error) => {
+                self.insert_msg(&error, true);
+            }
+        }
+        if let Err(error) = Loader::load_css(true) {
+            self.insert_msg(&error, true);
+        }
+        handler.populate();
+        Some(())
+    }
     pub fn obfuscate(&self, vis: bool) -> Option<()> {
         let ui = self.search_ui.as_ref().and_then(|ui| ui.upgrade())?;
         let imp = ui.imp();
@@ -163,10 +369,16 @@ impl SherlockAPI {
         let model = handler.model.as_ref().and_then(|s| s.upgrade())?;
         handler.clear();
 
-        let data = Tile::pipe_data(&content, "print");
-        data.into_iter().for_each(|elem| {
-            model.append(&elem);
-        });
+        let data = Tile::pipe_data(&content, "print", 0);
+        model.extend_from_slice(&data);
+        Some(())
+    }
+    /// Adds rows to the current pipe view without clearing it. The current selection is kept.
+    pub fn append_pipe(&self, content: Vec<PipedElements>, first_index: usize) -> Option<()> {
+        let handler = self.search_handler.as_ref()?;
+        let model = handler.model.as_ref().and_then(|s| s.upgrade())?;
+        let data = Tile::pipe_data(&content, "print", first_index);
+        model.extend_from_slice(&data);
         Some(())
     }
     pub fn insert_msg(&self, error: &SherlockError, is_error: bool) -> Option<()> {
@@ -178,6 +390,11 @@ impl SherlockAPI {
         let model = self.errors.as_ref().and_then(|tmp| tmp.upgrade())?;
         let (_, tiles) = Tile::error_tile(0, &vec![error], icon, msg);
         model.append(tiles.first()?);
+        SherlockEvent::ErrorInserted {
+            error: error.to_string(),
+            is_error,
+        }
+        .emit();
         Some(())
     }
 
@@ -189,12 +406,28 @@ impl SherlockAPI {
         } else {
             None
         };
-        if let Some(elements) = elements {
-            self.display_pipe(elements);
-            self.switch_page("search-page");
-        }
+        let elements = elements?;
+        self.pipe_lines = elements.len();
+        self.display_pipe(elements)?;
+        self.switch_page("search-page");
         Some(())
     }
+    fn append_pipe_elements<T: AsRef<[u8]>>(&mut self, msg: T) -> Option<()> {
+        let first_index = self.pipe_lines;
+        let elements = PipedData::deserialize_pipe(&msg)?;
+        self.pipe_lines += elements.len();
+        self.append_pipe(elements, first_index)
+    }
+    fn add_bind(&self, bind: &PipeBind) -> Option<()> {
+        let handler = self.search_handler.as_ref()?;
+        handler.binds.add_custom(bind);
+        Some(())
+    }
+    fn set_spinner(&self, active: bool) -> Option<()> {
+        let ui = self.search_ui.as_ref().and_then(|ui| ui.upgrade())?;
+        ui.activate_action("win.spinner-mode", Some(&active.to_variant()))
+            .ok()
+    }
     fn display_raw<T: AsRef<str>>(&mut self, msg: T) -> Option<()> {
         let config = CONFIG.get()?;
         let stack = self.stack.as_ref().and_then(|tmp| tmp.upgrade())?;
@@ -240,12 +473,24 @@ impl SherlockAPI {
         win.present();
         Some(())
     }
+    /// Enters the launcher mode with `alias` without touching the query. Unknown aliases
+    /// enter the `all` mode, just like the `switch-mode` action does.
+    fn enter_alias(&self, alias: &str) -> Option<()> {
+        let ui = self.search_ui.as_ref().and_then(|ui| ui.upgrade())?;
+        let _ = ui.activate_action("win.switch-mode", Some(&alias.to_variant()));
+        let _ = ui.activate_action("win.update-items", Some(&true.to_variant()));
+        self.switch_page("search-page");
+        Some(())
+    }
     pub fn switch_mode(&mut self, mode: &SherlockModes) -> Option<()> {
         match mode {
             SherlockModes::Search => {
                 self.search_view()?;
                 self.switch_page("search-page");
             }
+            SherlockModes::Alias(alias) => {
+                self.enter_alias(alias)?;
+            }
             SherlockModes::Pipe(pipe) => {
                 self.load_pipe_elements(pipe)?;
             }
@@ -267,6 +512,8 @@ impl SherlockAPI {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub enum SherlockModes {
     Search,
+    /// The launcher mode with this alias, e.g. `pm`
+    Alias(String),
     Error,
     DisplayRaw(String),
     Pipe(String),
@@ -276,6 +523,7 @@ impl Display for SherlockModes {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Search => write!(f, "SearchView"),
+            Self::Alias(alias) => write!(f, "Mode:{}", alias),
             Self::Error => write!(f, "ErrorView"),
             Self::Pipe(_) => write!(f, "PipeView"),
             Self::DisplayRaw(_) => write!(f, "RawView"),
@@ -283,3 +531,56 @@ impl Display for SherlockModes {
         }
     }
 }
+
+/// Splits a leading mode alias (e.g. `pm `) off `query` and returns it without its trailing
+/// space, along with the rest of the query. The longest alias wins if several match.
+fn split_mode_alias<'a, 'q>(
+    modes: &'a HashMap<String, Option<String>>,
+    query: &'q str,
+) -> (Option<&'a str>, &'q str) {
+    let alias = modes
+        .keys()
+        .filter(|alias| query.starts_with(alias.as_str()))
+        .max_by_key(|alias| alias.len());
+    match alias {
+        Some(alias) => (Some(alias.trim_end()), &query[alias.len()..]),
+        None => (None, query),
+    }
+}
+
+/// Clamps `index` to the last of `n_items` rows. Returns `None` if there are no rows.
+fn clamp_index(index: u32, n_items: u32) -> Option<u32> {
+    n_items.checked_sub(1).map(|last| index.min(last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_the_selected_index() {
+        assert_eq!(clamp_index(1, 3), Some(1));
+        assert_eq!(clamp_index(2, 3), Some(2));
+        assert_eq!(clamp_index(5, 3), Some(2));
+        assert_eq!(clamp_index(0, 0), None);
+    }
+
+    #[test]
+    fn picks_the_longest_mode_alias() {
+        let modes: HashMap<String, Option<String>> = ["p ", "p m ", "web "]
+            .into_iter()
+            .map(|alias| (alias.to_string(), None))
+            .collect();
+        assert_eq!(
+            split_mode_alias(&modes, "p m firefox"),
+            (Some("p m"), "firefox")
+        );
+        assert_eq!(
+            split_mode_alias(&modes, "p firefox"),
+            (Some("p"), "firefox")
+        );
+        assert_eq!(split_mode_alias(&modes, "web "), (Some("web"), ""));
+        assert_eq!(split_mode_alias(&modes, "pm firefox"), (None, "pm firefox"));
+        assert_eq!(split_mode_alias(&modes, "web"), (None, "web"));
+    }
+}
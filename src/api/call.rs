@@ -1,15 +1,18 @@
 use std::fmt::Display;
 
-use crate::utils::errors::SherlockError;
+use crate::utils::{config::PipeBind, errors::SherlockError};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use zbus::zvariant::Type;
 
-use super::api::SherlockModes;
+use super::{api::SherlockModes, events::EventKind};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub enum ApiCall {
     // Settings
     InputOnly,
     Obfuscate(bool),
+    Bind(PipeBind),
     // Actions
     Socket(Option<String>),
     Show,
@@ -18,8 +21,34 @@ pub enum ApiCall {
     SherlockWarning(SherlockError),
     ClearAwaiting,
     Pipe(String),
+    AppendPipe(String),
+    PipeOpen(bool),
     DisplayRaw(String),
     SwitchMode(SherlockModes),
+    SetQuery(String),
+    SelectIndex(u32),
+    ActivateSelected,
+    Close,
+    Reload,
+    OpenWith(Vec<String>),
+    // Queries
+    GetMode,
+    GetQuery,
+    ListModes,
+    ListRows,
+    GetSelected,
+    // Connection
+    Subscribe(Vec<EventKind>),
+}
+impl ApiCall {
+    /// Whether the call only reads state. Queries are answered through the reply of an
+    /// [`ApiRequest`] and never queued.
+    pub fn is_query(&self) -> bool {
+        matches!(
+            self,
+            Self::GetMode | Self::GetQuery | Self::ListModes | Self::ListRows | Self::GetSelected
+        )
+    }
 }
 impl Display for ApiCall {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -27,6 +56,7 @@ impl Display for ApiCall {
             // Settings
             Self::InputOnly => write!(f, "setting.InputOnly"),
             Self::Obfuscate(val) => write!(f, "setting.Obfuscate:{}", val),
+            Self::Bind(bind) => write!(f, "setting.Bind:{}", bind.key),
             // Actions
             Self::Show => write!(f, "action.Show"),
             Self::Socket(socket) => write!(f, "action.Socket:{:?}", socket),
@@ -35,8 +65,92 @@ impl Display for ApiCall {
             Self::SherlockWarning(err) => write!(f, "action.InsertWarning:{}", err),
             Self::ClearAwaiting => write!(f, "action.ClearAwaiting"),
             Self::Pipe(pipe) => write!(f, "action.ProcessPipe:{}", pipe),
+            Self::AppendPipe(pipe) => write!(f, "action.AppendPipe:{}", pipe),
+            Self::PipeOpen(open) => write!(f, "action.PipeOpen:{}", open),
             Self::DisplayRaw(pipe) => write!(f, "action.DisplayRaw:{}", pipe),
             Self::SwitchMode(mode) => write!(f, "action.SwitchMode:{}", mode),
+            Self::SetQuery(query) => write!(f, "action.SetQuery:{}", query),
+            Self::SelectIndex(index) => write!(f, "action.SelectIndex:{}", index),
+            Self::ActivateSelected => write!(f, "action.ActivateSelected"),
+            Self::Close => write!(f, "action.Close"),
+            Self::Reload => write!(f, "action.Reload"),
+            Self::OpenWith(files) => write!(f, "action.OpenWith:{}", files.join(" ")),
+            // Queries
+            Self::GetMode => write!(f, "query.GetMode"),
+            Self::GetQuery => write!(f, "query.GetQuery"),
+            Self::ListModes => write!(f, "query.ListModes"),
+            Self::ListRows => write!(f, "query.ListRows"),
+            Self::GetSelected => write!(f, "query.GetSelected"),
+            // Connection
+            Self::Subscribe(kinds) => write!(f, "connection.Subscribe:{:?}", kinds),
         }
     }
 }
+
+/// An [`ApiCall`] wrapped in an envelope. The daemon answers it with an [`ApiResponse`]
+/// carrying the same `id` on the connection the request came from.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ApiRequest {
+    pub id: u64,
+    pub call: ApiCall,
+}
+
+/// A row as reported by the `ListRows` and `GetSelected` queries
+#[derive(Debug, Deserialize, Serialize, Clone, Default, Type)]
+pub struct RowInfo {
+    pub title: String,
+    pub launcher: String,
+    pub priority: f32,
+    pub visible: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ApiResponse {
+    pub id: u64,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+impl ApiResponse {
+    pub fn success(id: u64, result: Option<Value>) -> Self {
+        Self {
+            id,
+            ok: true,
+            result,
+            error: None,
+        }
+    }
+    pub fn failure<T: Into<String>>(id: u64, error: T) -> Self {
+        Self {
+            id,
+            ok: false,
+            result: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelopes_and_bare_calls_are_distinguishable() {
+        let request: ApiRequest = serde_json::from_str(r#"{"id":7,"call":"Show"}"#).unwrap();
+        assert_eq!(request.id, 7);
+        assert!(matches!(request.call, ApiCall::Show));
+        assert!(serde_json::from_str::<ApiCall>(r#"{"id":7,"call":"Show"}"#).is_err());
+        assert!(serde_json::from_str::<ApiRequest>(r#""Show""#).is_err());
+        assert!(serde_json::from_str::<ApiRequest>(r#"{"Obfuscate":true}"#).is_err());
+    }
+
+    #[test]
+    fn responses_omit_empty_fields() {
+        let response = serde_json::to_string(&ApiResponse::success(1, None)).unwrap();
+        assert_eq!(response, r#"{"id":1,"ok":true}"#);
+        let response = serde_json::to_string(&ApiResponse::failure(2, "queued")).unwrap();
+        assert_eq!(response, r#"{"id":2,"ok":false,"error":"queued"}"#);
+    }
+}
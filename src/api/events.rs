@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// The kinds of events a client can subscribe to
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    WindowShown,
+    WindowHidden,
+    QueryChanged,
+    ModeSwitched,
+    RowActivated,
+    ErrorInserted,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(tag = "event")]
+pub enum SherlockEvent {
+    WindowShown,
+    WindowHidden,
+    QueryChanged { query: String },
+    ModeSwitched { mode: String },
+    RowActivated { attrs: HashMap<String, String> },
+    ErrorInserted { error: String, is_error: bool },
+}
+/// Row attributes holding typed or piped content, which may be secret
+const PRIVATE_ATTRS: [&str; 3] = ["keyword", "result", "output"];
+
+impl SherlockEvent {
+    /// Reports an activated row without the attributes listed in [`PRIVATE_ATTRS`]
+    pub fn row_activated(attrs: &HashMap<String, String>) -> Self {
+        let attrs = attrs
+            .iter()
+            .filter(|(key, _)| !PRIVATE_ATTRS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        Self::RowActivated { attrs }
+    }
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Self::WindowShown => EventKind::WindowShown,
+            Self::WindowHidden => EventKind::WindowHidden,
+            Self::QueryChanged { .. } => EventKind::QueryChanged,
+            Self::ModeSwitched { .. } => EventKind::ModeSwitched,
+            Self::RowActivated { .. } => EventKind::RowActivated,
+            Self::ErrorInserted { .. } => EventKind::ErrorInserted,
+        }
+    }
+    /// Sends the event to every subscriber interested in its kind
+    pub fn emit(self) {
+        EVENTS.publish(&self);
+    }
+}
+
+struct Subscriber {
+    kinds: Vec<EventKind>,
+    sender: async_channel::Sender<String>,
+}
+
+/// Forwards events to subscribed connections. Every event is sent as a single line of JSON.
+pub struct EventBus {
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+impl EventBus {
+    pub const fn new() -> Self {
+        Self {
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+    /// Registers a subscriber for `kinds`. An empty list subscribes to all events.
+    pub fn subscribe(&self, kinds: Vec<EventKind>, sender: async_channel::Sender<String>) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(Subscriber { kinds, sender });
+        }
+    }
+    /// Sends `event` to all matching subscribers and drops the ones that disconnected
+    pub fn publish(&self, event: &SherlockEvent) {
+        let Ok(mut subscribers) = self.subscribers.lock() else {
+            return;
+        };
+        subscribers.retain(|sub| !sub.sender.is_closed());
+        if subscribers.is_empty() {
+            return;
+        }
+        let Ok(line) = serde_json::to_string(event) else {
+            return;
+        };
+        let kind = event.kind();
+        subscribers
+            .iter()
+            .filter(|sub| sub.kinds.is_empty() || sub.kinds.contains(&kind))
+            .for_each(|sub| {
+                let _ = sub.sender.try_send(line.clone());
+            });
+    }
+}
+
+pub static EVENTS: EventBus = EventBus::new();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_reach_matching_subscribers() {
+        let bus = EventBus::new();
+        let (all, all_events) = async_channel::unbounded();
+        let (queries, query_events) = async_channel::unbounded();
+        bus.subscribe(vec![], all);
+        bus.subscribe(vec![EventKind::QueryChanged], queries);
+
+        bus.publish(&SherlockEvent::WindowShown);
+        bus.publish(&SherlockEvent::QueryChanged {
+            query: String::from("sus"),
+        });
+
+        assert_eq!(all_events.try_recv().unwrap(), r#"{"event":"WindowShown"}"#);
+        assert_eq!(
+            all_events.try_recv().unwrap(),
+            r#"{"event":"QueryChanged","query":"sus"}"#
+        );
+        assert_eq!(
+            query_events.try_recv().unwrap(),
+            r#"{"event":"QueryChanged","query":"sus"}"#
+        );
+        assert!(query_events.try_recv().is_err());
+
+        // Disconnected subscribers are dropped on the next event
+        drop(query_events);
+        bus.publish(&SherlockEvent::WindowHidden);
+        assert_eq!(bus.subscribers.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn activated_rows_omit_typed_content() {
+        let attrs: HashMap<String, String> = [
+            ("method", "web_launcher"),
+            ("keyword", "hunter2"),
+            ("result", "hunter2"),
+            ("output", "hunter2"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        let SherlockEvent::RowActivated { attrs } = SherlockEvent::row_activated(&attrs) else {
+            panic!("expected a RowActivated event");
+        };
+        assert_eq!(
+            attrs,
+            HashMap::from([(String::from("method"), String::from("web_launcher"))])
+        );
+    }
+}
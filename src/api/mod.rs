@@ -1,3 +1,5 @@
 pub mod api;
 pub mod call;
+pub mod events;
+pub mod msg;
 pub mod server;
@@ -0,0 +1,196 @@
+use std::io::{self, Read};
+
+use crate::{
+    sherlock_error,
+    utils::{
+        config::PipeBind,
+        errors::{SherlockError, SherlockErrorType},
+    },
+};
+
+use super::{api::SherlockModes, call::ApiCall, server::SherlockServer};
+
+/// Sends the call described by the arguments of `sherlock msg` to the running instance and
+/// prints its reply. Returns the exit code.
+pub fn send_message(args: &[String]) -> i32 {
+    let result = parse_message(args).and_then(SherlockServer::request);
+    match result {
+        Ok(response) => {
+            if let Ok(reply) = serde_json::to_string(&response) {
+                println!("{}", reply);
+            }
+            if response.ok {
+                0
+            } else {
+                1
+            }
+        }
+        Err(error) => {
+            eprintln!("{}\n{}", error, error.traceback);
+            1
+        }
+    }
+}
+
+/// Builds an [`ApiCall`] from a call name in kebab-case and its arguments, e.g.
+/// `switch-mode pm`. A call can also be given as JSON. `pipe`, `append-pipe` and `display-raw`
+/// read their content from stdin if it is not passed as an argument.
+pub fn parse_message(args: &[String]) -> Result<ApiCall, SherlockError> {
+    let (name, rest) = args
+        .split_first()
+        .ok_or_else(|| invalid("No call given. Usage: sherlock msg <call> [args]"))?;
+    if name.starts_with('{') || name.starts_with('"') {
+        return serde_json::from_str(name)
+            .map_err(|e| sherlock_error!(SherlockErrorType::DeserializationError, e.to_string()));
+    }
+    let joined = rest.join(" ");
+    let call = match name.as_str() {
+        "show" => ApiCall::Show,
+        "close" => ApiCall::Close,
+        "clear" => ApiCall::Clear,
+        "reload" => ApiCall::Reload,
+        "input-only" => ApiCall::InputOnly,
+        "clear-awaiting" => ApiCall::ClearAwaiting,
+        "activate-selected" => ApiCall::ActivateSelected,
+        "get-mode" => ApiCall::GetMode,
+        "get-query" => ApiCall::GetQuery,
+        "list-modes" => ApiCall::ListModes,
+        "list-rows" => ApiCall::ListRows,
+        "get-selected" => ApiCall::GetSelected,
+        "obfuscate" => ApiCall::Obfuscate(match joined.as_str() {
+            "" | "true" => true,
+            "false" => false,
+            _ => return Err(invalid("obfuscate expects true or false")),
+        }),
+        "set-query" => ApiCall::SetQuery(joined),
+        "bind" => ApiCall::Bind(
+            PipeBind::parse(&joined)
+                .ok_or_else(|| invalid("bind expects KEY=ACTION[:EXIT_CODE]"))?,
+        ),
+        "select-index" => ApiCall::SelectIndex(
+            joined
+                .parse()
+                .map_err(|_| invalid("select-index expects a row index"))?,
+        ),
+        "switch-mode" if joined.is_empty() => return Err(invalid("switch-mode expects a mode")),
+        "switch-mode" => ApiCall::SwitchMode(parse_mode(&joined)),
+        "open-with" if rest.is_empty() => return Err(invalid("open-with expects files or URLs")),
+        "open-with" => ApiCall::OpenWith(rest.iter().map(|file| absolute(file)).collect()),
+        "pipe" => ApiCall::Pipe(content_or_stdin(joined)?),
+        "append-pipe" => ApiCall::AppendPipe(content_or_stdin(joined)?),
+        "display-raw" => ApiCall::DisplayRaw(content_or_stdin(joined)?),
+        _ => return Err(invalid(format!("Unknown call \"{}\"", name))),
+    };
+    Ok(call)
+}
+
+/// Parses a mode name as given to `switch-mode`. Names other than `search`, `error`, `input`
+/// and `obfuscated-input` are taken as the alias of a launcher mode.
+pub fn parse_mode(name: &str) -> SherlockModes {
+    match name {
+        "search" => SherlockModes::Search,
+        "error" => SherlockModes::Error,
+        "input" => SherlockModes::Input(false),
+        "obfuscated-input" => SherlockModes::Input(true),
+        alias => SherlockModes::Alias(alias.to_string()),
+    }
+}
+
+/// Resolves relative paths against the working directory of `sherlock msg`. URLs are kept.
+fn absolute(file: &str) -> String {
+    if file.contains("://") {
+        return file.to_string();
+    }
+    std::path::absolute(file).map_or_else(
+        |_| file.to_string(),
+        |path| path.to_string_lossy().to_string(),
+    )
+}
+
+fn content_or_stdin(content: String) -> Result<String, SherlockError> {
+    if !content.is_empty() {
+        return Ok(content);
+    }
+    let mut buf = String::new();
+    io::stdin()
+        .read_to_string(&mut buf)
+        .map_err(|e| sherlock_error!(SherlockErrorType::FlagLoadError, e.to_string()))?;
+    Ok(buf)
+}
+
+fn invalid<T: AsRef<str>>(message: T) -> SherlockError {
+    sherlock_error!(SherlockErrorType::FlagLoadError, message.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::cli::MSG_CALLS;
+
+    fn parse(args: &[&str]) -> Result<ApiCall, SherlockError> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        parse_message(&args)
+    }
+
+    #[test]
+    fn parses_calls_and_arguments() {
+        assert!(matches!(parse(&["show"]), Ok(ApiCall::Show)));
+        assert!(matches!(
+            parse(&["set-query", "fire", "fox"]),
+            Ok(ApiCall::SetQuery(q)) if q == "fire fox"
+        ));
+        assert!(matches!(
+            parse(&["switch-mode", "pm"]),
+            Ok(ApiCall::SwitchMode(SherlockModes::Alias(alias))) if alias == "pm"
+        ));
+        assert!(matches!(
+            parse(&["switch-mode", "search"]),
+            Ok(ApiCall::SwitchMode(SherlockModes::Search))
+        ));
+        assert!(matches!(
+            parse(&["switch-mode", "obfuscated-input"]),
+            Ok(ApiCall::SwitchMode(SherlockModes::Input(true)))
+        ));
+        assert!(matches!(
+            parse(&["select-index", "3"]),
+            Ok(ApiCall::SelectIndex(3))
+        ));
+        assert!(matches!(
+            parse(&["obfuscate", "false"]),
+            Ok(ApiCall::Obfuscate(false))
+        ));
+        assert!(matches!(
+            parse(&[r#"{"SelectIndex":2}"#]),
+            Ok(ApiCall::SelectIndex(2))
+        ));
+
+        assert!(parse(&[]).is_err());
+        assert!(matches!(
+            parse(&["bind", "alt-1=delete:10"]),
+            Ok(ApiCall::Bind(bind)) if bind.exit == Some(10)
+        ));
+
+        assert!(matches!(
+            parse(&["open-with", "/tmp/a b.pdf", "https://example.com"]),
+            Ok(ApiCall::OpenWith(files)) if files == ["/tmp/a b.pdf", "https://example.com"]
+        ));
+        assert!(matches!(
+            parse(&["open-with", "notes.md"]),
+            Ok(ApiCall::OpenWith(files)) if files[0].starts_with('/') && files[0].ends_with("/notes.md")
+        ));
+        assert!(parse(&["open-with"]).is_err());
+
+        assert!(parse(&["select-index", "two"]).is_err());
+        assert!(parse(&["switch-mode"]).is_err());
+        assert!(parse(&["explode"]).is_err());
+    }
+
+    #[test]
+    fn completed_calls_are_known() {
+        for call in MSG_CALLS {
+            if let Err(error) = parse(&[call, "1"]) {
+                assert!(!error.traceback.contains("Unknown call"), "{}", call);
+            }
+        }
+    }
+}
@@ -1,62 +1,115 @@
 use gio::glib::MainContext;
-use std::{cell::RefCell, os::unix::net::UnixStream, rc::Rc, thread};
+use std::{
+    cell::RefCell,
+    os::unix::net::UnixStream,
+    rc::Rc,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use crate::{
-    daemon::daemon::{SherlockDaemon, SizedMessage},
+    daemon::{
+        daemon::{DaemonMessage, SherlockDaemon, SizedMessage},
+        dbus::SherlockBus,
+    },
     loader::pipe_loader::PipedData,
-    sher_log, sherlock_error,
+    runtime_paths, sher_log, sherlock_error,
     utils::errors::{SherlockError, SherlockErrorType},
-    CONFIG, SOCKET_PATH,
+    CONFIG,
+};
+
+use super::{
+    api::SherlockAPI,
+    call::{ApiCall, ApiRequest, ApiResponse},
 };
 
-use super::{api::SherlockAPI, call::ApiCall};
+/// The requests the server forwards to [`SherlockAPI`]
+pub trait ApiHandler {
+    fn handle_request(&mut self, request: ApiRequest) -> ApiResponse;
+    fn await_request(&mut self, request: ApiCall) -> Option<()>;
+    fn flush(&mut self) -> Option<()>;
+}
+impl ApiHandler for SherlockAPI {
+    fn handle_request(&mut self, request: ApiRequest) -> ApiResponse {
+        SherlockAPI::handle_request(self, request)
+    }
+    fn await_request(&mut self, request: ApiCall) -> Option<()> {
+        SherlockAPI::await_request(self, request)
+    }
+    fn flush(&mut self) -> Option<()> {
+        SherlockAPI::flush(self)
+    }
+}
 
 pub struct SherlockServer;
 impl SherlockServer {
     pub fn listen(api: Rc<RefCell<SherlockAPI>>) {
         // Create async pipeline
         let (sender, receiver) = async_channel::bounded(1);
-        thread::spawn(move || {
-            async_std::task::block_on(async {
-                let _daemon = SherlockDaemon::new(sender).await;
-            });
+        tokio::spawn({
+            let sender = sender.clone();
+            async move {
+                if let Err(error) = SherlockBus::serve(sender).await {
+                    sher_log!(format!("D-Bus service unavailable: {}", error));
+                }
+            }
+        });
+        tokio::spawn(async move {
+            if let Err(error) = SherlockDaemon::run(sender).await {
+                sher_log!(format!("Daemon unavailable: {}", error));
+            }
         });
 
         // Handle receiving using pipline
         MainContext::default().spawn_local({
             async move {
-                while let Ok(msg) = receiver.recv().await {
-                    if let Ok(cmd) = serde_json::from_str::<ApiCall>(&msg) {
-                        sher_log!(format!("Incoming api request: {}", cmd));
-                        api.borrow_mut().await_request(cmd);
-                    } else if let Some(mut data) = PipedData::new(&msg) {
-                        if let Some(settings) = data.settings.take() {
-                            let mut api = api.borrow_mut();
-                            settings.into_iter().for_each(|request| {
-                                api.await_request(request);
-                            });
-                        }
-                        if let Some(elements) = data.elements.take() {
-                            let raw = CONFIG.get().map_or(false, |c| c.runtime.display_raw);
-                            let request = if raw {
-                                ApiCall::DisplayRaw(elements)
-                            } else {
-                                ApiCall::Pipe(elements)
-                            };
-                            api.borrow_mut().await_request(request);
-                        }
-                    } else {
-                        sher_log!(format!("Failed to deserialize api call(s): {}", msg));
+                while let Ok(DaemonMessage { content, reply }) = receiver.recv().await {
+                    let response = Self::dispatch(&mut *api.borrow_mut(), &content);
+                    if let Some(response) = response.and_then(|r| serde_json::to_string(&r).ok()) {
+                        let _ = reply.send(response).await;
                     }
-                    api.borrow_mut().flush();
                 }
             }
         });
     }
+    /// Hands a received message to the api. Enveloped calls are handled right away and
+    /// answered, while bare calls and piped data are queued.
+    pub fn dispatch<T: ApiHandler>(api: &mut T, msg: &str) -> Option<ApiResponse> {
+        let mut response = None;
+        if let Ok(request) = serde_json::from_str::<ApiRequest>(msg) {
+            sher_log!(format!(
+                "Incoming api request {}: {}",
+                request.id, request.call
+            ));
+            response = Some(api.handle_request(request));
+        } else if let Ok(cmd) = serde_json::from_str::<ApiCall>(msg) {
+            sher_log!(format!("Incoming api request: {}", cmd));
+            api.await_request(cmd);
+        } else if let Some(mut data) = PipedData::new(msg) {
+            if let Some(settings) = data.settings.take() {
+                settings.into_iter().for_each(|request| {
+                    api.await_request(request);
+                });
+            }
+            if let Some(elements) = data.elements.take() {
+                let raw = CONFIG.get().map_or(false, |c| c.runtime.display_raw);
+                let request = if raw {
+                    ApiCall::DisplayRaw(elements)
+                } else {
+                    ApiCall::Pipe(elements)
+                };
+                api.await_request(request);
+            }
+        } else {
+            sher_log!(format!("Failed to deserialize api call(s): {}", msg));
+        }
+        api.flush();
+        response
+    }
     pub fn _send<T: AsRef<[u8]>>(message: T) -> Result<(), SherlockError> {
-        let mut stream = UnixStream::connect(SOCKET_PATH).map_err(|e| {
+        let socket = runtime_paths().socket.as_str();
+        let mut stream = UnixStream::connect(socket).map_err(|e| {
             sherlock_error!(
-                SherlockErrorType::SocketConnectError(SOCKET_PATH.to_string()),
+                SherlockErrorType::SocketConnectError(socket.to_string()),
                 e.to_string()
             )
         })?;
@@ -64,9 +117,10 @@ impl SherlockServer {
         Ok(())
     }
     pub fn send_action(api_call: ApiCall) -> Result<(), SherlockError> {
-        let mut stream = UnixStream::connect(SOCKET_PATH).map_err(|e| {
+        let socket = runtime_paths().socket.as_str();
+        let mut stream = UnixStream::connect(socket).map_err(|e| {
             sherlock_error!(
-                SherlockErrorType::SocketConnectError(SOCKET_PATH.to_string()),
+                SherlockErrorType::SocketConnectError(socket.to_string()),
                 e.to_string()
             )
         })?;
@@ -75,4 +129,149 @@ impl SherlockServer {
         stream.write_sized(msg.as_bytes())?;
         Ok(())
     }
+    /// Sends `api_call` to the running instance and waits for its reply
+    pub fn request(api_call: ApiCall) -> Result<ApiResponse, SherlockError> {
+        Self::request_on(&runtime_paths().socket, api_call)
+    }
+    pub fn request_on(socket: &str, api_call: ApiCall) -> Result<ApiResponse, SherlockError> {
+        let mut stream = UnixStream::connect(socket).map_err(|e| {
+            sherlock_error!(
+                SherlockErrorType::SocketConnectError(socket.to_string()),
+                e.to_string()
+            )
+        })?;
+        let id = REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+        let request = ApiRequest { id, call: api_call };
+        let msg = serde_json::to_string(&request)
+            .map_err(|e| sherlock_error!(SherlockErrorType::SerializationError, e.to_string()))?;
+        stream.write_sized(msg.as_bytes())?;
+
+        let reply = stream.read_sized()?;
+        let response: ApiResponse = serde_json::from_slice(&reply)
+            .map_err(|e| sherlock_error!(SherlockErrorType::DeserializationError, e.to_string()))?;
+        if response.id != id {
+            return Err(sherlock_error!(
+                SherlockErrorType::DeserializationError,
+                format!("Expected reply to request {} but got {}", id, response.id)
+            ));
+        }
+        Ok(response)
+    }
+}
+
+static REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::events::EVENTS, utils::test_api::MockApi};
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    /// Serves a daemon on a temporary socket and answers through [`SherlockServer::dispatch`]
+    async fn serve(name: &str, api: Arc<Mutex<MockApi>>) -> String {
+        let socket = std::env::temp_dir()
+            .join(format!(
+                "sherlock-test-{}-{}.socket",
+                name,
+                std::process::id()
+            ))
+            .to_string_lossy()
+            .to_string();
+        let (sender, receiver) = async_channel::unbounded();
+        tokio::spawn({
+            let socket = socket.clone();
+            async move {
+                SherlockDaemon::serve(&socket, sender, &EVENTS)
+                    .await
+                    .unwrap();
+            }
+        });
+        MockApi::answer(api, receiver);
+        for _ in 0..100 {
+            if UnixStream::connect(&socket).is_ok() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        socket
+    }
+
+    async fn request(socket: &str, call: ApiCall) -> ApiResponse {
+        let socket = socket.to_string();
+        tokio::task::spawn_blocking(move || SherlockServer::request_on(&socket, call))
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn remote_control_calls_are_answered() {
+        let api = Arc::new(Mutex::new(MockApi::default()));
+        let socket = serve("remote-control", Arc::clone(&api)).await;
+
+        let calls = vec![
+            ApiCall::SetQuery(String::from("pm sus")),
+            ApiCall::SelectIndex(1),
+            ApiCall::SelectIndex(5),
+            ApiCall::ActivateSelected,
+            ApiCall::Close,
+            ApiCall::Reload,
+        ];
+        for call in calls {
+            let response = request(&socket, call).await;
+            assert!(response.ok);
+            assert!(response.error.is_none());
+        }
+
+        let response = request(&socket, ApiCall::GetMode).await;
+        assert!(!response.ok);
+        assert_eq!(
+            response.error.as_deref(),
+            Some("query.GetMode is not available yet")
+        );
+
+        let response = request(&socket, ApiCall::GetQuery).await;
+        assert_eq!(response.result, Some(json!("sus")));
+
+        assert_eq!(
+            api.lock().unwrap().handled,
+            vec![
+                "action.SetQuery:pm sus",
+                "action.SelectIndex:1",
+                "action.SelectIndex:5",
+                "action.ActivateSelected",
+                "action.Close",
+                "action.Reload",
+                "query.GetMode",
+                "query.GetQuery",
+            ]
+        );
+        let _ = std::fs::remove_file(&socket);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn bare_calls_are_queued_without_reply() {
+        let api = Arc::new(Mutex::new(MockApi::default()));
+        let socket = serve("bare-calls", Arc::clone(&api)).await;
+
+        let mut stream = UnixStream::connect(&socket).unwrap();
+        stream.write_sized(br#"{"SetQuery":"sus"}"#).unwrap();
+        stream.write_sized(br#""Close""#).unwrap();
+        // An enveloped call on the same connection is the first to get a reply
+        stream
+            .write_sized(br#"{"id":42,"call":"ActivateSelected"}"#)
+            .unwrap();
+        let reply = tokio::task::spawn_blocking(move || stream.read_sized().unwrap())
+            .await
+            .unwrap();
+        let response: ApiResponse = serde_json::from_slice(&reply).unwrap();
+        assert_eq!(response.id, 42);
+        assert!(response.ok);
+
+        let api = api.lock().unwrap();
+        assert_eq!(api.queued, vec!["action.SetQuery:sus", "action.Close"]);
+        assert_eq!(api.handled, vec!["action.ActivateSelected"]);
+        let _ = std::fs::remove_file(&socket);
+    }
 }
@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use crate::{
+    api::{
+        call::{ApiCall, ApiResponse, RowInfo},
+        msg::send_message,
+        server::SherlockServer,
+    },
+    sherlock_error,
+    utils::{
+        cli::SubCommand,
+        config::{SherlockConfig, SherlockFlags},
+        errors::{SherlockError, SherlockErrorType},
+    },
+};
+
+/// Runs a subcommand instead of starting Sherlock. Returns the exit code.
+pub fn run(command: &SubCommand, flags: &SherlockFlags) -> i32 {
+    let result = match command {
+        SubCommand::Init(dir) => init(dir.clone()),
+        SubCommand::Msg(args) => return send_message(args),
+        SubCommand::Check => check(flags),
+        SubCommand::Query(query) => query_rows(query),
+    };
+    match result {
+        Ok(_) => 0,
+        Err(error) => {
+            eprintln!("{}\n{}", error, error.traceback);
+            1
+        }
+    }
+}
+
+fn init(dir: Option<PathBuf>) -> Result<(), SherlockError> {
+    let dir = dir.unwrap_or(PathBuf::from("~/.config/sherlock/"));
+    SherlockConfig::to_file(dir.clone())?;
+    println!("Wrote default configs into {}", dir.display());
+    Ok(())
+}
+
+/// Loads the config like a normal start would and reports every problem found
+fn check(flags: &SherlockFlags) -> Result<(), SherlockError> {
+    let (_, warnings) = SherlockConfig::from_flags(flags)?;
+    for warning in &warnings {
+        eprintln!("{}\n{}", warning, warning.traceback);
+    }
+    println!("Config is valid ({} warnings)", warnings.len());
+    Ok(())
+}
+
+/// Types `query` into the running instance and prints the matching rows as JSON lines
+fn query_rows(query: &str) -> Result<(), SherlockError> {
+    request(ApiCall::SetQuery(query.to_string()))?;
+    let response = request(ApiCall::ListRows)?;
+    let rows: Vec<RowInfo> = response
+        .result
+        .and_then(|result| serde_json::from_value(result).ok())
+        .unwrap_or_default();
+    let mut rows: Vec<RowInfo> = rows.into_iter().filter(|row| row.visible).collect();
+    rows.sort_by(|a, b| a.priority.total_cmp(&b.priority));
+    for row in rows {
+        if let Ok(line) = serde_json::to_string(&row) {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+/// Like [`SherlockServer::request`], but treats a rejected call as an error
+fn request(call: ApiCall) -> Result<ApiResponse, SherlockError> {
+    let name = call.to_string();
+    let response = SherlockServer::request(call)?;
+    if !response.ok {
+        return Err(sherlock_error!(
+            SherlockErrorType::CommandExecutionError(name),
+            response.error.unwrap_or_default()
+        ));
+    }
+    Ok(response)
+}
@@ -14,7 +14,9 @@ pub fn ensure_single_instance(lock_file: &str) -> Result<LockFile, String> {
             if let Some(pid) = content.parse::<i32>().ok() {
                 match Process::new(pid) {
                     Ok(_) => {
-                        let _ = SherlockDaemon::instance();
+                        if let Err(error) = SherlockDaemon::instance() {
+                            eprintln!("{}\n{}", error, error.traceback);
+                        }
                     }
                     Err(_) => {
                         let _ = fs::remove_file(lock_file);
@@ -1,38 +1,83 @@
+use nix::errno::Errno;
+use nix::sys::socket::{getsockopt, sockopt::PeerCredentials};
+use nix::unistd::getuid;
 use std::fs::remove_file;
 use std::io::{Read, Write};
+use std::os::fd::AsFd;
 use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::thread;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 use crate::api::api::RESPONSE_SOCKET;
-use crate::api::call::ApiCall;
+use crate::api::call::{ApiCall, ApiRequest, ApiResponse};
+use crate::api::events::{EventBus, EventKind, EVENTS};
+use crate::loader::pipe_loader::PipedData;
 use crate::loader::Loader;
 use crate::utils::errors::{SherlockError, SherlockErrorType};
-use crate::{sher_log, sherlock_error, SOCKET_DIR, SOCKET_PATH};
+use crate::{runtime_paths, sher_log, sherlock_error};
+
+/// Upper bound for a single message. Piped content is sent as one message, so this has to
+/// leave room for large inputs while still protecting the daemon from bogus length headers.
+pub const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// A message received by the daemon. Anything sent through `reply` is written back to the
+/// connection the message came from.
+pub struct DaemonMessage {
+    pub content: String,
+    pub reply: async_channel::Sender<String>,
+}
 
 pub struct SherlockDaemon {
     socket: String,
 }
 impl SherlockDaemon {
-    pub async fn new(pipeline: async_channel::Sender<String>) -> Self {
-        let _ = std::fs::remove_file(SOCKET_PATH);
-        let listener = UnixListener::bind(SOCKET_PATH).expect("Failed to bind socket");
-        sher_log!(format!("Daemon listening on {}", SOCKET_PATH));
-
-        for stream in listener.incoming() {
-            if let Ok(mut stream) = stream {
-                loop {
-                    match stream.read_sized() {
-                        Ok(buf) if !buf.is_empty() => {
-                            let received_data = String::from_utf8_lossy(&buf);
-                            let received_data = received_data.trim();
-                            let _ = pipeline.send(received_data.to_string()).await;
-                        }
-                        Ok(_) | Err(_) => break,
+    /// Serves the daemon socket of this instance
+    pub async fn run(pipeline: async_channel::Sender<DaemonMessage>) -> Result<(), SherlockError> {
+        let socket = runtime_paths().socket.as_str();
+        sher_log!(format!("Daemon listening on {}", socket));
+        Self::serve(socket, pipeline, &EVENTS).await
+    }
+    /// Accepts connections on `socket` until the task is dropped and spawns a task per client,
+    /// so that a slow client cannot block others. Every received message is forwarded into `pipeline`, subscribers
+    /// receive the events published on `events`.
+    pub async fn serve(
+        socket: &str,
+        pipeline: async_channel::Sender<DaemonMessage>,
+        events: &'static EventBus,
+    ) -> Result<(), SherlockError> {
+        let _ = std::fs::remove_file(socket);
+        let listener = tokio::net::UnixListener::bind(socket).map_err(|e| {
+            sherlock_error!(
+                SherlockErrorType::SocketConnectError(socket.to_string()),
+                e.to_string()
+            )
+        })?;
+
+        // Removes the socket once serving stops
+        let _daemon = Self {
+            socket: socket.to_string(),
+        };
+
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(error) => {
+                    sher_log!(format!("Failed to accept connection: {}", error));
+                    // Give other connections time to close if we ran out of file descriptors
+                    let errno = error.raw_os_error().map(Errno::from_raw);
+                    if matches!(errno, Some(Errno::EMFILE | Errno::ENFILE)) {
+                        tokio::time::sleep(Duration::from_millis(100)).await;
                     }
+                    continue;
                 }
+            };
+            if let Err(error) = verify_peer(&stream, socket) {
+                sher_log!(format!("Rejected connection: {}", error));
+                continue;
             }
-        }
-        Self {
-            socket: SOCKET_PATH.to_string(),
+            tokio::spawn(handle_connection(stream, pipeline.clone(), events));
         }
     }
     fn remove(&self) -> Result<(), SherlockError> {
@@ -45,25 +90,35 @@ impl SherlockDaemon {
         Ok(())
     }
     pub fn instance() -> Result<(), SherlockError> {
-        let mut stream = UnixStream::connect(SOCKET_PATH).map_err(|e| {
+        let socket = runtime_paths().socket.as_str();
+        let mut stream = UnixStream::connect(socket).map_err(|e| {
             sherlock_error!(
-                SherlockErrorType::SocketConnectError(SOCKET_PATH.to_string()),
+                SherlockErrorType::SocketConnectError(socket.to_string()),
                 e.to_string()
             )
         })?;
         // Send pipe request
-        let pipe = Loader::load_pipe_args();
+        let receiver = Loader::stream_pipe_args();
+        let mut pipe = receiver
+            .as_ref()
+            .and_then(|r| r.recv_blocking().ok())
+            .unwrap_or_default();
         if pipe.is_empty() {
             stream.write_sized(br#""Show""#)?;
         } else {
             // Send return pipe request
-            let addr = format!("{}sherlock-pipe.socket", SOCKET_DIR);
+            let addr = runtime_paths().pipe_socket.clone();
 
             // remove existing socket
             let _ = remove_file(&addr);
 
             // create new socket
-            let listener = UnixListener::bind(&addr).expect("Failed to bind socket for responses.");
+            let listener = UnixListener::bind(&addr).map_err(|e| {
+                sherlock_error!(
+                    SherlockErrorType::SocketConnectError(addr.clone()),
+                    e.to_string()
+                )
+            })?;
 
             // tell sherlock to use this socket
             let request = ApiCall::Socket(Some(addr));
@@ -72,12 +127,25 @@ impl SherlockDaemon {
             })?;
             stream.write_sized(request_json.as_bytes())?;
 
+            // JSON documents can only be sent once stdin is closed
+            let streaming = PipedData::is_streamable(&pipe);
+            if !streaming {
+                while let Some(chunk) = receiver.as_ref().and_then(|r| r.recv_blocking().ok()) {
+                    pipe.extend(chunk);
+                }
+            }
+
             // Send piped content and show
             stream.write_sized(&pipe)?;
             stream.write_sized(br#""Show""#)?;
 
-            // Close so it wont block main
-            drop(stream);
+            // Forward the remaining input while waiting for the response
+            match receiver.filter(|_| streaming) {
+                Some(receiver) => {
+                    thread::spawn(move || Self::forward_pipe(stream, receiver));
+                }
+                None => drop(stream),
+            }
 
             // Await response
             'server_loop: for stream in listener.incoming() {
@@ -90,6 +158,12 @@ impl SherlockDaemon {
                                 if received_data == "EXIT" {
                                     break 'server_loop;
                                 }
+                                if let Some(code) = received_data
+                                    .strip_prefix("EXIT ")
+                                    .and_then(|code| code.parse::<i32>().ok())
+                                {
+                                    std::process::exit(code);
+                                }
                                 println!("{}", received_data);
                             }
                             Ok(_) | Err(_) => break,
@@ -100,6 +174,158 @@ impl SherlockDaemon {
         }
         Ok(())
     }
+    fn forward_pipe(
+        mut stream: UnixStream,
+        receiver: async_channel::Receiver<Vec<u8>>,
+    ) -> Result<(), SherlockError> {
+        let send = |stream: &mut UnixStream, call: ApiCall| {
+            let msg = simd_json::to_string(&call).map_err(|e| {
+                sherlock_error!(SherlockErrorType::SerializationError, e.to_string())
+            })?;
+            stream.write_sized(msg.as_bytes())
+        };
+        send(&mut stream, ApiCall::PipeOpen(true))?;
+        while let Ok(mut batch) = receiver.recv_blocking() {
+            while let Ok(chunk) = receiver.try_recv() {
+                batch.extend(chunk);
+            }
+            let pipe = String::from_utf8_lossy(&batch).to_string();
+            send(&mut stream, ApiCall::AppendPipe(pipe))?;
+        }
+        send(&mut stream, ApiCall::PipeOpen(false))
+    }
+}
+
+/// Reads messages from a single client until it disconnects or sends an invalid message.
+/// Replies are written by a separate task, which finishes once all pending replies are sent.
+/// A `Subscribe` call turns the connection into an event stream.
+async fn handle_connection(
+    stream: tokio::net::UnixStream,
+    pipeline: async_channel::Sender<DaemonMessage>,
+    events: &'static EventBus,
+) {
+    let (mut reader, mut writer) = stream.into_split();
+    let (reply, replies) = async_channel::unbounded::<String>();
+    let replier = tokio::spawn(async move {
+        while let Ok(response) = replies.recv().await {
+            if write_sized_async(&mut writer, response.as_bytes())
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+        writer
+    });
+
+    let mut subscription = None;
+    loop {
+        match read_sized_async(&mut reader).await {
+            Ok(buf) if !buf.is_empty() => {
+                let received_data = String::from_utf8_lossy(&buf);
+                let content = received_data.trim().to_string();
+                if let Some(request) = parse_subscription(&content) {
+                    subscription = Some(request);
+                    break;
+                }
+                let message = DaemonMessage {
+                    content,
+                    reply: reply.clone(),
+                };
+                if pipeline.send(message).await.is_err() {
+                    break;
+                }
+            }
+            Ok(_) | Err(_) => break,
+        }
+    }
+
+    if let Some((id, kinds)) = subscription {
+        // Let pending replies go out before the connection switches to events
+        drop(reply);
+        if let Ok(writer) = replier.await {
+            stream_events(reader, writer, id, kinds, events).await;
+        }
+    }
+}
+
+/// Returns the request id, if any, and the event kinds of a `Subscribe` call
+fn parse_subscription(content: &str) -> Option<(Option<u64>, Vec<EventKind>)> {
+    if let Ok(ApiRequest {
+        id,
+        call: ApiCall::Subscribe(kinds),
+    }) = serde_json::from_str::<ApiRequest>(content)
+    {
+        return Some((Some(id), kinds));
+    }
+    match serde_json::from_str::<ApiCall>(content) {
+        Ok(ApiCall::Subscribe(kinds)) => Some((None, kinds)),
+        _ => None,
+    }
+}
+
+/// Writes subscribed events as newline-delimited JSON until the client disconnects. An
+/// enveloped `Subscribe` call is acknowledged with its response as the first line.
+async fn stream_events(
+    mut reader: tokio::net::unix::OwnedReadHalf,
+    mut writer: tokio::net::unix::OwnedWriteHalf,
+    id: Option<u64>,
+    kinds: Vec<EventKind>,
+    bus: &EventBus,
+) {
+    let (sender, events) = async_channel::unbounded::<String>();
+    bus.subscribe(kinds, sender);
+
+    if let Some(id) = id {
+        let Ok(ack) = serde_json::to_string(&ApiResponse::success(id, None)) else {
+            return;
+        };
+        if writer
+            .write_all(format!("{}\n", ack).as_bytes())
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+
+    let mut buf = [0u8; 64];
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let Ok(line) = event else { break };
+                if writer.write_all(format!("{}\n", line).as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+            // Anything the client sends after subscribing is ignored
+            read = reader.read(&mut buf) => {
+                if matches!(read, Ok(0) | Err(_)) {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Only accepts connections from processes running as the same user as Sherlock
+fn verify_peer<T: AsFd>(stream: &T, socket: &str) -> Result<(), SherlockError> {
+    let credentials = getsockopt(stream, PeerCredentials).map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::SocketConnectError(socket.to_string()),
+            e.to_string()
+        )
+    })?;
+    if credentials.uid() != getuid().as_raw() {
+        return Err(sherlock_error!(
+            SherlockErrorType::SocketConnectError(socket.to_string()),
+            format!(
+                "Peer is running as a different user (uid {})",
+                credentials.uid()
+            )
+        ));
+    }
+    Ok(())
 }
 
 impl Drop for SherlockDaemon {
@@ -108,26 +334,50 @@ impl Drop for SherlockDaemon {
     }
 }
 
+/// Encodes the length prefix for a message, rejecting messages above [`MAX_MESSAGE_SIZE`]
+fn encode_len(len: usize) -> Result<[u8; 4], SherlockError> {
+    if len > MAX_MESSAGE_SIZE {
+        return Err(sherlock_error!(
+            SherlockErrorType::SocketWriteError(runtime_paths().socket.clone()),
+            format!(
+                "Message of {} bytes exceeds the limit of {} bytes",
+                len, MAX_MESSAGE_SIZE
+            )
+        ));
+    }
+    Ok((len as u32).to_be_bytes())
+}
+/// Decodes a length prefix, rejecting messages above [`MAX_MESSAGE_SIZE`]
+fn decode_len(header: [u8; 4]) -> Result<usize, SherlockError> {
+    let len = u32::from_be_bytes(header) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        return Err(sherlock_error!(
+            SherlockErrorType::SocketReadError(runtime_paths().socket.clone()),
+            format!(
+                "Message of {} bytes exceeds the limit of {} bytes",
+                len, MAX_MESSAGE_SIZE
+            )
+        ));
+    }
+    Ok(len)
+}
+
 pub trait SizedMessage {
     fn write_sized(&mut self, buf: &[u8]) -> Result<(), SherlockError>;
     fn read_sized(&mut self) -> Result<Vec<u8>, SherlockError>;
 }
 impl SizedMessage for UnixStream {
     fn write_sized(&mut self, buf: &[u8]) -> Result<(), SherlockError> {
-        let buf_len = buf.len();
-        if buf_len > u32::MAX as usize {
-            // return error for size too big
-        }
-        let buf_len = buf_len as u32;
-        self.write_all(&buf_len.to_be_bytes()).map_err(|e| {
+        let header = encode_len(buf.len())?;
+        self.write_all(&header).map_err(|e| {
             sherlock_error!(
-                SherlockErrorType::SocketWriteError(SOCKET_PATH.to_string()),
+                SherlockErrorType::SocketWriteError(runtime_paths().socket.clone()),
                 e.to_string()
             )
         })?;
         self.write_all(buf).map_err(|e| {
             sherlock_error!(
-                SherlockErrorType::SocketWriteError(SOCKET_PATH.to_string()),
+                SherlockErrorType::SocketWriteError(runtime_paths().socket.clone()),
                 e.to_string()
             )
         })?;
@@ -135,19 +385,19 @@ impl SizedMessage for UnixStream {
         Ok(())
     }
     fn read_sized(&mut self) -> Result<Vec<u8>, SherlockError> {
-        let mut buf_len = [0u8; 4];
-        self.read_exact(&mut buf_len).map_err(|e| {
+        let mut header = [0u8; 4];
+        self.read_exact(&mut header).map_err(|e| {
             sherlock_error!(
-                SherlockErrorType::SocketWriteError(SOCKET_PATH.to_string()),
+                SherlockErrorType::SocketReadError(runtime_paths().socket.clone()),
                 e.to_string()
             )
         })?;
-        let msg_len = u32::from_be_bytes(buf_len) as usize;
+        let msg_len = decode_len(header)?;
 
         let mut buf = vec![0u8; msg_len];
         self.read_exact(&mut buf).map_err(|e| {
             sherlock_error!(
-                SherlockErrorType::SocketWriteError(SOCKET_PATH.to_string()),
+                SherlockErrorType::SocketReadError(runtime_paths().socket.clone()),
                 e.to_string()
             )
         })?;
@@ -156,7 +406,91 @@ impl SizedMessage for UnixStream {
     }
 }
 
+/// Async counterpart of [`SizedMessage::write_sized`]
+pub async fn write_sized_async<T: AsyncWrite + Unpin>(
+    stream: &mut T,
+    buf: &[u8],
+) -> Result<(), SherlockError> {
+    let header = encode_len(buf.len())?;
+    stream.write_all(&header).await.map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::SocketWriteError(runtime_paths().socket.clone()),
+            e.to_string()
+        )
+    })?;
+    stream.write_all(buf).await.map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::SocketWriteError(runtime_paths().socket.clone()),
+            e.to_string()
+        )
+    })?;
+    Ok(())
+}
+/// Async counterpart of [`SizedMessage::read_sized`]
+pub async fn read_sized_async<T: AsyncRead + Unpin>(
+    stream: &mut T,
+) -> Result<Vec<u8>, SherlockError> {
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).await.map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::SocketReadError(runtime_paths().socket.clone()),
+            e.to_string()
+        )
+    })?;
+    let msg_len = decode_len(header)?;
+
+    let mut buf = vec![0u8; msg_len];
+    stream.read_exact(&mut buf).await.map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::SocketReadError(runtime_paths().socket.clone()),
+            e.to_string()
+        )
+    })?;
+    Ok(buf)
+}
+
+/// Set once a response has been printed, so dmenu mode can tell a cancelled selection apart
+static RESPONSE_PRINTED: AtomicBool = AtomicBool::new(false);
+
+pub fn response_printed() -> bool {
+    RESPONSE_PRINTED.load(Ordering::Relaxed)
+}
+
+/// Exit code requested by a custom binding of pipe mode. A binding only stages its code, which
+/// takes effect once a row activated by it closes the window.
+pub struct ExitCode {
+    staged: AtomicI32,
+    code: AtomicI32,
+}
+impl ExitCode {
+    pub const fn new() -> Self {
+        Self {
+            staged: AtomicI32::new(0),
+            code: AtomicI32::new(0),
+        }
+    }
+    /// Stages `code` for the rows activated next. Staging 0 withdraws it.
+    pub fn stage(&self, code: i32) {
+        self.staged.store(code, Ordering::Relaxed);
+    }
+    /// Applies the staged code when an activated row closes the window
+    pub fn commit(&self) {
+        self.code
+            .store(self.staged.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+    pub fn get(&self) -> i32 {
+        self.code.load(Ordering::Relaxed)
+    }
+    /// Returns the exit code and resets it for the next instance
+    pub fn take(&self) -> i32 {
+        self.code.swap(0, Ordering::Relaxed)
+    }
+}
+
+pub static EXIT_CODE: ExitCode = ExitCode::new();
+
 pub fn print_reponse<T: AsRef<[u8]>>(response: T) -> Result<(), SherlockError> {
+    RESPONSE_PRINTED.store(true, Ordering::Relaxed);
     let guard = RESPONSE_SOCKET.read().unwrap();
     let response = response.as_ref();
     if let Some(addr) = guard.as_ref() {
@@ -183,7 +517,12 @@ pub fn close_response() -> Result<(), SherlockError> {
                     e.to_string()
                 )
             })?;
-            stream.write_sized(b"EXIT")?;
+            // Forward the exit code to the waiting instance and reset it for the next one
+            let message = match EXIT_CODE.take() {
+                0 => String::from("EXIT"),
+                code => format!("EXIT {}", code),
+            };
+            stream.write_sized(message.as_bytes())?;
         }
     }
     {
@@ -193,3 +532,145 @@ pub fn close_response() -> Result<(), SherlockError> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::timeout;
+
+    async fn connect(socket: &str) -> UnixStream {
+        for _ in 0..100 {
+            if let Ok(stream) = UnixStream::connect(socket) {
+                return stream;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("daemon did not start listening on {}", socket);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serves_clients_concurrently() {
+        let socket = std::env::temp_dir()
+            .join(format!(
+                "sherlock-test-daemon-{}.socket",
+                std::process::id()
+            ))
+            .to_string_lossy()
+            .to_string();
+        let (sender, receiver) = async_channel::unbounded();
+        let server = tokio::spawn({
+            let socket = socket.clone();
+            async move {
+                SherlockDaemon::serve(&socket, sender, &EVENTS)
+                    .await
+                    .unwrap();
+            }
+        });
+
+        // The first client stalls in the middle of its length prefix
+        let mut slow = connect(&socket).await;
+        slow.write_all(&[0, 0]).unwrap();
+
+        let mut fast = connect(&socket).await;
+        fast.write_sized(br#""Show""#).unwrap();
+        let received = timeout(Duration::from_secs(2), receiver.recv()).await;
+        let message = received.unwrap().unwrap();
+        assert_eq!(message.content, r#""Show""#);
+
+        // Replies go back to the connection the message came from
+        message.reply.send(String::from("done")).await.unwrap();
+        assert_eq!(fast.read_sized().unwrap(), b"done");
+
+        slow.write_all(&[0, 5]).unwrap();
+        slow.write_all(b"Clear").unwrap();
+        let received = timeout(Duration::from_secs(2), receiver.recv()).await;
+        assert_eq!(received.unwrap().unwrap().content, "Clear");
+
+        server.abort();
+        let _ = remove_file(&socket);
+    }
+
+    #[test]
+    fn rejects_oversized_messages() {
+        let (mut client, mut server) = UnixStream::pair().unwrap();
+        client.write_all(&u32::MAX.to_be_bytes()).unwrap();
+        assert!(server.read_sized().is_err());
+
+        let message = vec![0u8; MAX_MESSAGE_SIZE + 1];
+        assert!(client.write_sized(&message).is_err());
+
+        client.write_sized(b"Show").unwrap();
+        assert_eq!(server.read_sized().unwrap(), b"Show");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn subscribers_receive_events() {
+        use crate::api::events::SherlockEvent;
+        use std::io::{BufRead, BufReader};
+
+        // A bus of its own keeps events of other tests out of the stream
+        static BUS: EventBus = EventBus::new();
+
+        let socket = std::env::temp_dir()
+            .join(format!(
+                "sherlock-test-events-{}.socket",
+                std::process::id()
+            ))
+            .to_string_lossy()
+            .to_string();
+        let (sender, _receiver) = async_channel::unbounded();
+        let server = tokio::spawn({
+            let socket = socket.clone();
+            async move {
+                SherlockDaemon::serve(&socket, sender, &BUS).await.unwrap();
+            }
+        });
+
+        let mut stream = connect(&socket).await;
+        stream
+            .write_sized(br#"{"id":3,"call":{"Subscribe":["QueryChanged","RowActivated"]}}"#)
+            .unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        assert_eq!(lines.next().unwrap().unwrap(), r#"{"id":3,"ok":true}"#);
+
+        BUS.publish(&SherlockEvent::WindowShown);
+        BUS.publish(&SherlockEvent::QueryChanged {
+            query: String::from("sus"),
+        });
+        BUS.publish(&SherlockEvent::RowActivated {
+            attrs: [(String::from("method"), String::from("app_launcher"))].into(),
+        });
+
+        assert_eq!(
+            lines.next().unwrap().unwrap(),
+            r#"{"event":"QueryChanged","query":"sus"}"#
+        );
+        assert_eq!(
+            lines.next().unwrap().unwrap(),
+            r#"{"event":"RowActivated","attrs":{"method":"app_launcher"}}"#
+        );
+
+        server.abort();
+        let _ = remove_file(&socket);
+    }
+
+    #[test]
+    fn bind_exit_codes_need_an_activation() {
+        let exit = ExitCode::new();
+        // The bind had no row to activate, so a later Return or Escape exits normally
+        exit.stage(10);
+        exit.stage(0);
+        exit.commit();
+        assert_eq!(exit.get(), 0);
+
+        // The bind activated a row that closed the window
+        exit.stage(10);
+        exit.commit();
+        exit.stage(0);
+        assert_eq!(exit.take(), 10);
+        assert_eq!(exit.get(), 0);
+    }
+}
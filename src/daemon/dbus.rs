@@ -0,0 +1,304 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use zbus::{connection::Builder, fdo, interface, object_server::SignalEmitter, Connection};
+
+use crate::api::{
+    call::{ApiCall, ApiRequest, ApiResponse, RowInfo},
+    events::{SherlockEvent, EVENTS},
+    msg::parse_mode,
+};
+use crate::sherlock_error;
+use crate::utils::errors::{SherlockError, SherlockErrorType};
+
+use super::daemon::DaemonMessage;
+
+pub const BUS_NAME: &str = "dev.skxxtz.Sherlock";
+pub const OBJECT_PATH: &str = "/dev/skxxtz/Sherlock";
+
+/// Exposes the api on the session bus. Every method call is forwarded as an [`ApiRequest`]
+/// into the same pipeline the daemon socket uses and answered with its reply.
+pub struct SherlockBus {
+    pipeline: async_channel::Sender<DaemonMessage>,
+    next_id: AtomicU64,
+}
+impl SherlockBus {
+    /// Claims [`BUS_NAME`] on the session bus and emits a signal for every event until the
+    /// event bus shuts down
+    pub async fn serve(
+        pipeline: async_channel::Sender<DaemonMessage>,
+    ) -> Result<(), SherlockError> {
+        let builder = Builder::session()
+            .map_err(|e| sherlock_error!(SherlockErrorType::DBusConnectionError, e.to_string()))?;
+        let connection = Self::connect(builder, pipeline).await?;
+
+        let (sender, events) = async_channel::unbounded();
+        EVENTS.subscribe(vec![], sender);
+        Self::forward_events(&connection, events).await
+    }
+    pub async fn connect(
+        builder: Builder<'_>,
+        pipeline: async_channel::Sender<DaemonMessage>,
+    ) -> Result<Connection, SherlockError> {
+        let bus = Self {
+            pipeline,
+            next_id: AtomicU64::new(1),
+        };
+        builder
+            .name(BUS_NAME)
+            .and_then(|builder| builder.serve_at(OBJECT_PATH, bus))
+            .map_err(|e| sherlock_error!(SherlockErrorType::DBusConnectionError, e.to_string()))?
+            .build()
+            .await
+            .map_err(|e| sherlock_error!(SherlockErrorType::DBusConnectionError, e.to_string()))
+    }
+    /// Turns serialized [`SherlockEvent`]s into signals on `connection`
+    pub async fn forward_events(
+        connection: &Connection,
+        events: async_channel::Receiver<String>,
+    ) -> Result<(), SherlockError> {
+        let iface = connection
+            .object_server()
+            .interface::<_, Self>(OBJECT_PATH)
+            .await
+            .map_err(|e| sherlock_error!(SherlockErrorType::DBusConnectionError, e.to_string()))?;
+        while let Ok(line) = events.recv().await {
+            if let Ok(event) = serde_json::from_str::<SherlockEvent>(&line) {
+                let _ = emit(iface.signal_emitter(), event).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn request(&self, call: ApiCall) -> fdo::Result<Option<Value>> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let content = serde_json::to_string(&ApiRequest { id, call })
+            .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        let (reply, replies) = async_channel::bounded(1);
+        self.pipeline
+            .send(DaemonMessage { content, reply })
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        let response = replies
+            .recv()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        let response: ApiResponse =
+            serde_json::from_str(&response).map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        if response.ok {
+            Ok(response.result)
+        } else {
+            Err(fdo::Error::Failed(response.error.unwrap_or_default()))
+        }
+    }
+    async fn query<T: DeserializeOwned>(&self, call: ApiCall) -> fdo::Result<T> {
+        let result = self.request(call).await?.unwrap_or_default();
+        serde_json::from_value(result).map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+}
+
+#[interface(name = "dev.skxxtz.Sherlock")]
+impl SherlockBus {
+    // Actions
+    async fn show(&self) -> fdo::Result<()> {
+        self.request(ApiCall::Show).await.map(|_| ())
+    }
+    async fn close(&self) -> fdo::Result<()> {
+        self.request(ApiCall::Close).await.map(|_| ())
+    }
+    async fn clear(&self) -> fdo::Result<()> {
+        self.request(ApiCall::Clear).await.map(|_| ())
+    }
+    async fn reload(&self) -> fdo::Result<()> {
+        self.request(ApiCall::Reload).await.map(|_| ())
+    }
+    async fn input_only(&self) -> fdo::Result<()> {
+        self.request(ApiCall::InputOnly).await.map(|_| ())
+    }
+    async fn obfuscate(&self, obfuscate: bool) -> fdo::Result<()> {
+        self.request(ApiCall::Obfuscate(obfuscate))
+            .await
+            .map(|_| ())
+    }
+    async fn pipe(&self, content: String) -> fdo::Result<()> {
+        self.request(ApiCall::Pipe(content)).await.map(|_| ())
+    }
+    async fn open_with(&self, files: Vec<String>) -> fdo::Result<()> {
+        self.request(ApiCall::OpenWith(files)).await.map(|_| ())
+    }
+    async fn display_raw(&self, content: String) -> fdo::Result<()> {
+        self.request(ApiCall::DisplayRaw(content)).await.map(|_| ())
+    }
+    /// Accepts `search`, `error`, `input`, `obfuscated-input` and the alias of a launcher mode
+    async fn switch_mode(&self, mode: String) -> fdo::Result<()> {
+        if mode.is_empty() {
+            return Err(fdo::Error::InvalidArgs(String::from("No mode given")));
+        }
+        self.request(ApiCall::SwitchMode(parse_mode(&mode)))
+            .await
+            .map(|_| ())
+    }
+    async fn set_query(&self, query: String) -> fdo::Result<()> {
+        self.request(ApiCall::SetQuery(query)).await.map(|_| ())
+    }
+    async fn select_index(&self, index: u32) -> fdo::Result<()> {
+        self.request(ApiCall::SelectIndex(index)).await.map(|_| ())
+    }
+    async fn activate_selected(&self) -> fdo::Result<()> {
+        self.request(ApiCall::ActivateSelected).await.map(|_| ())
+    }
+
+    // Queries
+    /// Returns the alias and the title of the current mode
+    async fn get_mode(&self) -> fdo::Result<(String, String)> {
+        let mode: HashMap<String, String> = self.query(ApiCall::GetMode).await?;
+        let get = |key: &str| mode.get(key).cloned().unwrap_or_default();
+        Ok((get("mode"), get("title")))
+    }
+    async fn get_query(&self) -> fdo::Result<String> {
+        self.query(ApiCall::GetQuery).await
+    }
+    async fn list_modes(&self) -> fdo::Result<HashMap<String, String>> {
+        self.query(ApiCall::ListModes).await
+    }
+    async fn list_rows(&self) -> fdo::Result<Vec<RowInfo>> {
+        self.query(ApiCall::ListRows).await
+    }
+    /// Returns the index of the selected row among the visible rows and the row itself. The
+    /// index is -1 if nothing is selected.
+    async fn get_selected(&self) -> fdo::Result<(i32, RowInfo)> {
+        let selected: Option<Selected> = self.query(ApiCall::GetSelected).await?;
+        Ok(selected.map_or((-1, RowInfo::default()), |s| (s.index as i32, s.row)))
+    }
+
+    // Signals
+    #[zbus(signal)]
+    async fn shown(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+    #[zbus(signal)]
+    async fn hidden(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+    #[zbus(signal)]
+    async fn query_changed(emitter: &SignalEmitter<'_>, query: &str) -> zbus::Result<()>;
+    #[zbus(signal)]
+    async fn mode_switched(emitter: &SignalEmitter<'_>, mode: &str) -> zbus::Result<()>;
+    #[zbus(signal)]
+    async fn row_activated(
+        emitter: &SignalEmitter<'_>,
+        attrs: HashMap<String, String>,
+    ) -> zbus::Result<()>;
+    #[zbus(signal)]
+    async fn error_inserted(
+        emitter: &SignalEmitter<'_>,
+        error: &str,
+        is_error: bool,
+    ) -> zbus::Result<()>;
+}
+
+#[derive(serde::Deserialize)]
+struct Selected {
+    index: u32,
+    row: RowInfo,
+}
+
+async fn emit(emitter: &SignalEmitter<'_>, event: SherlockEvent) -> zbus::Result<()> {
+    match event {
+        SherlockEvent::WindowShown => SherlockBus::shown(emitter).await,
+        SherlockEvent::WindowHidden => SherlockBus::hidden(emitter).await,
+        SherlockEvent::QueryChanged { query } => SherlockBus::query_changed(emitter, &query).await,
+        SherlockEvent::ModeSwitched { mode } => SherlockBus::mode_switched(emitter, &mode).await,
+        SherlockEvent::RowActivated { attrs } => SherlockBus::row_activated(emitter, attrs).await,
+        SherlockEvent::ErrorInserted { error, is_error } => {
+            SherlockBus::error_inserted(emitter, &error, is_error).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{test_api::MockApi, test_bus::TestBus};
+    use futures::StreamExt;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn methods_and_signals_mirror_the_api() {
+        let Some(bus) = TestBus::start() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+        let (pipeline, requests) = async_channel::unbounded();
+        let api = Arc::new(Mutex::new(MockApi::default()));
+        MockApi::answer(Arc::clone(&api), requests);
+        let service = SherlockBus::connect(Builder::address(bus.address()).unwrap(), pipeline)
+            .await
+            .unwrap();
+        let (events, received) = async_channel::unbounded();
+        tokio::spawn(async move { SherlockBus::forward_events(&service, received).await });
+
+        let client = Builder::address(bus.address())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let proxy = zbus::Proxy::new(&client, BUS_NAME, OBJECT_PATH, BUS_NAME)
+            .await
+            .unwrap();
+
+        proxy
+            .call::<_, _, ()>("SetQuery", &("pm sus",))
+            .await
+            .unwrap();
+        proxy
+            .call::<_, _, ()>("ActivateSelected", &())
+            .await
+            .unwrap();
+        proxy
+            .call::<_, _, ()>("SelectIndex", &(5u32,))
+            .await
+            .unwrap();
+        let error = proxy
+            .call::<_, _, (String, String)>("GetMode", &())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not available yet"));
+        proxy
+            .call::<_, _, ()>("SwitchMode", &("pm",))
+            .await
+            .unwrap();
+        assert!(proxy.call::<_, _, ()>("SwitchMode", &("",)).await.is_err());
+
+        let query: String = proxy.call("GetQuery", &()).await.unwrap();
+        assert_eq!(query, "sus");
+        let (index, row): (i32, RowInfo) = proxy.call("GetSelected", &()).await.unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(row.title, "Firefox");
+
+        assert_eq!(
+            api.lock().unwrap().handled,
+            vec![
+                "action.SetQuery:pm sus",
+                "action.ActivateSelected",
+                "action.SelectIndex:5",
+                "query.GetMode",
+                "action.SwitchMode:Mode:pm",
+                "query.GetQuery",
+                "query.GetSelected",
+            ]
+        );
+
+        let mut activations = proxy.receive_signal("RowActivated").await.unwrap();
+        let event = SherlockEvent::RowActivated {
+            attrs: [(String::from("method"), String::from("app_launcher"))].into(),
+        };
+        events
+            .send(serde_json::to_string(&event).unwrap())
+            .await
+            .unwrap();
+        let signal = activations.next().await.unwrap();
+        let attrs: HashMap<String, String> = signal.body().deserialize().unwrap();
+        assert_eq!(
+            attrs.get("method").map(String::as_str),
+            Some("app_launcher")
+        );
+    }
+}
@@ -44,6 +44,7 @@ impl ContextAction {
                 let exec = exec.clone();
                 let method = action.method.clone();
                 let exit = action.exit.clone();
+                let icon = action.icon.clone();
                 move |row| {
                     let row = row.first().map(|f| f.get::<ContextAction>().ok())??;
                     let attrs = get_attrs_map(vec![
@@ -51,6 +52,7 @@ impl ContextAction {
                         ("exec", Some(&exec)),
                         ("term", Some(&terminal.to_string())),
                         ("exit", Some(&exit.to_string())),
+                        ("icon", icon.as_deref()),
                     ]);
                     execute_from_attrs(&row, &attrs, None);
                     // To reload ui according to mode
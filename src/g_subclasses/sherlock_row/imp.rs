@@ -12,6 +12,8 @@ use std::pin::Pin;
 use std::sync::OnceLock;
 
 use crate::loader::util::ApplicationAction;
+use crate::ui::preview::Preview;
+use crate::utils::fuzzy::FuzzyMatch;
 
 /// ## Fields:
 /// * **spawn_focus**: Whether the tile should receive focus when Sherlock starts.
@@ -20,8 +22,13 @@ use crate::loader::util::ApplicationAction;
 /// * **gesture**: State to hold and replace double-click gestures.
 /// * **shortcut_holder**: A `GtkBox` widget that holds the `modkey + number` shortcut indicators.
 /// * **priority**: Determines the tile's ordering within the `GtkListView`.
-/// * **search**: The string used to compute Levenshtein distance for this tile.
+/// * **search**: The string the search query is fuzzy matched against.
+/// * **search_match**: The result of the last fuzzy match of the search query against `search`.
+/// * **highlight_label**: The label in which matched characters get highlighted.
+/// * **counter_key**: The key under which activations of this tile are counted.
+/// * **learned**: How often this tile was activated for the current search query.
 /// * **alias**: The display mode in which this tile should appear.
+/// * **launcher**: The name of the launcher this tile belongs to.
 /// * **home**: Whether the tile should appear on the home screen (i.e., when the search entry is empty and mode is `all`).
 /// * **only_home**: Whether the tile should **only** appear on the home screen (i.e., when the search entry is empty and mode is `all`).
 /// * **disable**: Whether the tile be forced to not show.
@@ -30,6 +37,7 @@ use crate::loader::util::ApplicationAction;
 /// * **actions**: Additional actions this tile has
 /// * **num_actions**: Number of additional actions
 /// * **terminal**: If the app should be executed using the terminal
+/// * **preview**: The content shown in the preview pane while the tile is selected
 #[derive(Default)]
 pub struct SherlockRow {
     /// Whether the tile should receive focus when Sherlock starts  
@@ -53,12 +61,27 @@ pub struct SherlockRow {
     /// Determines the tile's ordering within the `GtkListView`  
     pub priority: Cell<f32>,
 
-    /// The string used to compute Levenshtein distance for this tile  
+    /// The string the search query is fuzzy matched against
     pub search: RefCell<String>,
 
+    /// The result of the last fuzzy match of the search query against `search`
+    pub search_match: RefCell<Option<FuzzyMatch>>,
+
+    /// The label in which matched characters get highlighted
+    pub highlight_label: OnceCell<WeakRef<gtk4::Label>>,
+
+    /// The key under which activations of this tile are counted
+    pub counter_key: RefCell<String>,
+
+    /// How often this tile was activated for the current search query
+    pub learned: Cell<u32>,
+
     /// The display mode in which this tile should appear  
     pub alias: RefCell<String>,
 
+    /// The name of the launcher this tile belongs to
+    pub launcher: RefCell<String>,
+
     /// Whether the tile should appear on the home screen  
     ///             (i.e. when the search entry is empty and mode is `all`)  
     pub home: Cell<bool>,
@@ -87,6 +110,9 @@ pub struct SherlockRow {
 
     /// * **terminal**: If this tile should be executed using the terminal
     pub terminal: Cell<bool>,
+
+    /// * **preview**: The content shown in the preview pane while the tile is selected
+    pub preview: RefCell<Option<Preview>>,
 }
 
 // The central trait for subclassing a GObject
@@ -112,7 +138,7 @@ impl ObjectImpl for SherlockRow {
                 if n_clicks >= 2 {
                     if let Some(obj) = obj.upgrade() {
                         let exit: u8 = 0;
-                        obj.emit_by_name::<()>("row-should-activate", &[&exit]);
+                        obj.emit_by_name::<()>("row-should-activate", &[&exit, &"click"]);
                     }
                 }
             });
@@ -123,10 +149,11 @@ impl ObjectImpl for SherlockRow {
     }
     fn signals() -> &'static [glib::subclass::Signal] {
         static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
-        // Signal used to activate actions connected to the SherlockRow
+        // Signal used to activate actions connected to the SherlockRow. The second parameter
+        // names what triggered the activation, e.g. `return` or `click`.
         SIGNALS.get_or_init(|| {
             vec![Signal::builder("row-should-activate")
-                .param_types([u8::static_type()])
+                .param_types([u8::static_type(), String::static_type()])
                 .build()]
         })
     }
@@ -5,11 +5,17 @@ use std::{cell::Ref, future::Future, pin::Pin};
 use gdk_pixbuf::subclass::prelude::ObjectSubclassIsExt;
 use gio::glib::{object::ObjectExt, GString, SignalHandlerId, WeakRef};
 use glib::Object;
-use gtk4::{glib, prelude::WidgetExt};
+use gtk4::{
+    glib,
+    prelude::{Cast, WidgetExt},
+    Label, Widget,
+};
 
 use crate::{
     launcher::Launcher,
     loader::util::{AppData, ApplicationAction},
+    ui::{preview::Preview, tiles::util::highlight_markup},
+    utils::fuzzy::FuzzyMatch,
 };
 
 glib::wrapper! {
@@ -55,7 +61,20 @@ impl SherlockRow {
         }
     }
     pub fn set_search(&self, search: &str) {
-        *self.imp().search.borrow_mut() = search.to_lowercase();
+        *self.imp().search.borrow_mut() = search.to_string();
+    }
+    pub fn set_search_match(&self, search_match: Option<FuzzyMatch>) {
+        *self.imp().search_match.borrow_mut() = search_match;
+        self.apply_highlight();
+    }
+    pub fn set_highlight_label(&self, label: WeakRef<Label>) {
+        let _ = self.imp().highlight_label.set(label);
+    }
+    pub fn set_counter_key(&self, key: &str) {
+        *self.imp().counter_key.borrow_mut() = key.to_string();
+    }
+    pub fn set_learned(&self, learned: u32) {
+        self.imp().learned.set(learned);
     }
     pub fn set_priority(&self, prio: f32) {
         self.imp().priority.set(prio);
@@ -63,6 +82,9 @@ impl SherlockRow {
     pub fn set_alias(&self, mode: &str) {
         *self.imp().alias.borrow_mut() = mode.to_string();
     }
+    pub fn set_launcher(&self, name: &str) {
+        *self.imp().launcher.borrow_mut() = name.to_string();
+    }
     pub fn set_home(&self, home: bool) {
         self.imp().home.set(home);
     }
@@ -116,6 +138,9 @@ impl SherlockRow {
     pub fn set_terminal(&self, term: bool) {
         self.imp().terminal.set(term);
     }
+    pub fn set_preview(&self, preview: Preview) {
+        *self.imp().preview.borrow_mut() = Some(preview);
+    }
 
     // getters
     pub fn shortcut_holder(&self) -> Option<gtk4::Box> {
@@ -130,6 +155,32 @@ impl SherlockRow {
     pub fn search(&self) -> String {
         self.imp().search.borrow().clone()
     }
+    pub fn launcher(&self) -> String {
+        self.imp().launcher.borrow().clone()
+    }
+    /// The displayed title of the tile. Falls back to the search string for tiles without a
+    /// `title` label.
+    pub fn title(&self) -> String {
+        let label = self
+            .imp()
+            .highlight_label
+            .get()
+            .and_then(|label| label.upgrade())
+            .or_else(|| find_title_label(self.upcast_ref()));
+        match label {
+            Some(label) => label.text().to_string(),
+            None => self.search(),
+        }
+    }
+    pub fn search_score(&self) -> Option<i32> {
+        self.imp().search_match.borrow().as_ref().map(|m| m.score)
+    }
+    pub fn counter_key(&self) -> Ref<String> {
+        self.imp().counter_key.borrow()
+    }
+    pub fn learned(&self) -> u32 {
+        self.imp().learned.get()
+    }
     pub fn priority(&self) -> f32 {
         self.imp().priority.get()
     }
@@ -165,6 +216,9 @@ impl SherlockRow {
     pub fn terminal(&self) -> bool {
         self.imp().terminal.get()
     }
+    pub fn preview(&self) -> Option<Preview> {
+        self.imp().preview.borrow().clone()
+    }
     /// Sets shared values from a launcher to the SherlockRow
     /// * only_home
     /// * home
@@ -180,6 +234,9 @@ impl SherlockRow {
         if let Some(alias) = &launcher.alias {
             self.set_alias(alias);
         }
+        if let Some(name) = launcher.name.as_ref().or(launcher.alias.as_ref()) {
+            self.set_launcher(name);
+        }
         if let Some(actions) = &launcher.actions {
             self.set_actions(actions.clone());
         }
@@ -189,12 +246,52 @@ impl SherlockRow {
     }
     pub fn with_appdata(&self, data: &AppData) {
         self.set_search(&data.search_string);
+        if let Some(exec) = &data.exec {
+            self.set_counter_key(exec);
+        }
         self.set_priority(data.priority);
         if !data.actions.is_empty() {
             self.set_actions(data.actions.clone());
         }
         self.set_terminal(data.terminal);
     }
+    /// Highlights the matched characters in the highlight label. Indices only map onto the
+    /// label if the search string starts with the displayed text (e.g. `name;keywords`).
+    fn apply_highlight(&self) -> Option<()> {
+        let imp = self.imp();
+        let label = imp.highlight_label.get()?.upgrade()?;
+        let text = label.text().to_string();
+        let len = text.chars().count();
+        let indices: Vec<usize> = match imp.search_match.borrow().as_ref() {
+            Some(m) if imp.search.borrow().starts_with(&text) => {
+                m.indices.iter().copied().filter(|i| *i < len).collect()
+            }
+            _ => vec![],
+        };
+        if indices.is_empty() {
+            label.set_text(&text);
+        } else {
+            label.set_markup(&highlight_markup(&text, &indices));
+        }
+        Some(())
+    }
+}
+
+/// Searches the widget tree for the label named `title` (or `title-label`)
+fn find_title_label(widget: &Widget) -> Option<Label> {
+    let mut child = widget.first_child();
+    while let Some(current) = child {
+        if let Some(label) = current.downcast_ref::<Label>() {
+            if matches!(label.widget_name().as_str(), "title" | "title-label") {
+                return Some(label.clone());
+            }
+        }
+        if let Some(label) = find_title_label(&current) {
+            return Some(label);
+        }
+        child = current.next_sibling();
+    }
+    None
 }
 
 impl Default for SherlockRow {
@@ -166,12 +166,18 @@ impl MozillaSqliteParser {
                             .map(|s| s.to_string()),
                         exec: Some(row.1),
                         search_string: row.0,
+                        comment: None,
                         tag_start: raw.tag_start.clone(),
                         tag_end: raw.tag_end.clone(),
                         desktop_file: None,
                         priority: raw.priority + 1.0,
                         actions: vec![],
+                        categories: vec![],
                         terminal: false,
+                        try_exec: None,
+                        dbus_activatable: false,
+                        working_dir: None,
+                        wm_class: None,
                     };
                     res.insert(bookmark);
                 }
@@ -256,12 +262,18 @@ impl ChromeParser {
                                 .map(|s| s.to_string()),
                             exec: Some(url.clone()),
                             search_string: format!("{};{}", bookmark.name, url),
+                            comment: None,
                             tag_start: raw.tag_start.clone(),
                             tag_end: raw.tag_end.clone(),
                             desktop_file: None,
                             priority: raw.priority + 1.0,
                             actions: vec![],
+                            categories: vec![],
                             terminal: false,
+                            try_exec: None,
+                            dbus_activatable: false,
+                            working_dir: None,
+                            wm_class: None,
                         });
                     }
                 }
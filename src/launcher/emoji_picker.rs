@@ -234,6 +234,7 @@ fn construct() -> Result<(Rc<RefCell<String>>, GridSearchUi, SearchHandler), She
         sorter.downgrade(),
         ConfKeys::new(),
         Cell::new(true),
+        Rc::new(RefCell::new(String::from("all"))),
     );
     Ok((search_text, ui, handler))
 }
@@ -339,7 +340,7 @@ fn make_filter(search_text: &Rc<RefCell<String>>) -> CustomFilter {
             }
             let item = entry.downcast_ref::<EmojiObject>().unwrap();
             let current_text = search_text.borrow().clone();
-            if item.title().fuzzy_match(&current_text) {
+            if item.title().fuzzy_match(&current_text).is_some() {
                 counter.set(current + 1);
                 return true;
             }
@@ -68,7 +68,7 @@ pub enum LauncherType {
 /// - **method:** Specifies the action that should be executed on `row-should-activate` action
 /// - **next_content:** Specifies the content to be displayed whenever method is `next`
 /// - **priority:** Base priority all children inherit from. Children priority will be a combination
-/// of this together with their execution counts and levenshtein similarity
+/// of this together with their execution counts and fuzzy match score
 /// - **r#async:** Specifies whether the tile should be loaded/executed asynchronously
 /// - **home:** Specifies whether the children should show on the `home` mode (empty
 /// search entry & mode == `all`)
@@ -1,11 +1,13 @@
 use async_std::sync::Mutex;
 use glob::Pattern;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use simd_json;
 use simd_json::prelude::ArrayTrait;
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs::{self, File};
+use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::SystemTime;
@@ -20,12 +22,24 @@ use crate::utils::{
 use crate::{sherlock_error, CONFIG};
 use util::{AppData, SherlockAlias};
 
+/// Version of the desktop file cache. Increment it whenever `AppData` or the way desktop
+/// files are parsed changes, so that existing caches get rebuilt.
+const CACHE_VERSION: u32 = 3;
+
+#[derive(Serialize, Deserialize)]
+struct AppCache<T> {
+    version: u32,
+    locales: Vec<String>,
+    /// `OnlyShowIn` and `NotShowIn` depend on the desktop the cache was built in
+    desktops: Vec<String>,
+    apps: T,
+}
+
 impl Loader {
     pub fn load_applications_from_disk(
         applications: Option<HashSet<PathBuf>>,
         priority: f32,
         counts: &HashMap<String, f32>,
-        decimals: i32,
     ) -> Result<HashSet<AppData>, SherlockError> {
         let config = CONFIG
             .get()
@@ -63,6 +77,12 @@ impl Loader {
         };
         let aliases = Arc::new(Mutex::new(aliases));
 
+        // Desktops used to evaluate 'OnlyShowIn' and 'NotShowIn'
+        let current_desktops = get_current_desktops();
+
+        // Locales used to pick localized keys like 'Name[de]'
+        let locales = get_locales();
+
         // Gather '.desktop' files
         let desktop_files: HashSet<PathBuf> = match applications {
             Some(apps) => apps,
@@ -79,6 +99,10 @@ impl Loader {
                         let mut data = AppData::new();
                         let mut current_section = None;
                         let mut current_action = ApplicationAction::new("app_launcher");
+                        let mut name = Localized::default();
+                        let mut generic_name = Localized::default();
+                        let mut comment = Localized::default();
+                        let mut keywords = Localized::default();
                         data.desktop_file = Some(entry);
                         for line in content.flatten() {
                             let line = line.trim();
@@ -98,18 +122,25 @@ impl Loader {
                                 continue;
                             }
                             if let Some((key, value)) = line.split_once('=') {
-                                let key = key.trim().to_ascii_lowercase();
+                                let (key, locale) = split_locale(key.trim());
+                                let key = key.to_ascii_lowercase();
                                 let value = value.trim();
                                 if current_section.as_deref().unwrap() == "Desktop Entry" {
+                                    let localized = match key.as_ref() {
+                                        "name" => Some(&mut name),
+                                        "genericname" => Some(&mut generic_name),
+                                        "comment" => Some(&mut comment),
+                                        "keywords" => Some(&mut keywords),
+                                        _ => None,
+                                    };
+                                    if let Some(localized) = localized {
+                                        localized.offer(locale, value, &locales);
+                                        continue;
+                                    }
+                                    if locale.is_some() {
+                                        continue;
+                                    }
                                     match key.as_ref() {
-                                        "name" => {
-                                            data.name = {
-                                                if should_ignore(&ignore_apps, value) {
-                                                    return None;
-                                                }
-                                                value.to_string()
-                                            }
-                                        }
                                         "icon" => data.icon = Some(value.to_string()),
                                         "exec" => data.exec = Some(value.to_string()),
                                         "nodisplay" if value.eq_ignore_ascii_case("true") => {
@@ -118,10 +149,43 @@ impl Loader {
                                         "terminal" => {
                                             data.terminal = value.eq_ignore_ascii_case("true");
                                         }
-                                        "keywords" => data.search_string = value.to_string(),
+                                        "hidden" if value.eq_ignore_ascii_case("true") => {
+                                            return None
+                                        }
+                                        "tryexec" => {
+                                            if !binary_exists(value) {
+                                                return None;
+                                            }
+                                            data.try_exec = Some(value.to_string());
+                                        }
+                                        "onlyshowin" => {
+                                            if !shown_in(value, &current_desktops) {
+                                                return None;
+                                            }
+                                        }
+                                        "notshowin" => {
+                                            if shown_in(value, &current_desktops) {
+                                                return None;
+                                            }
+                                        }
+                                        "path" if !value.is_empty() => {
+                                            data.working_dir = Some(value.to_string());
+                                        }
+                                        "startupwmclass" => data.wm_class = Some(value.to_string()),
+                                        "categories" => {
+                                            data.categories = value
+                                                .split(';')
+                                                .filter(|category| !category.is_empty())
+                                                .map(str::to_string)
+                                                .collect();
+                                        }
+                                        "dbusactivatable" => {
+                                            data.dbus_activatable =
+                                                value.eq_ignore_ascii_case("true");
+                                        }
                                         _ => {}
                                     }
-                                } else {
+                                } else if locale.is_none() {
                                     // Application Actions
                                     match key.as_ref() {
                                         "name" => current_action.name = Some(value.to_string()),
@@ -137,13 +201,33 @@ impl Loader {
                                 }
                             }
                         }
+                        // Apply the localized keys
+                        let default_name = name.default.as_deref().unwrap_or("");
+                        data.name = name.value().unwrap_or("").to_string();
+                        if should_ignore(&ignore_apps, default_name)
+                            || should_ignore(&ignore_apps, &data.name)
+                        {
+                            return None;
+                        }
+                        data.comment = comment.value().map(String::from);
+                        let mut search: Vec<&str> = Vec::with_capacity(4);
+                        if default_name != data.name {
+                            search.push(default_name);
+                        }
+                        search.extend(generic_name.value());
+                        search.extend(comment.value());
+                        search.extend(keywords.value());
+                        data.search_string = search.join(";");
+
                         data.actions
                             .iter_mut()
                             .filter(|action| action.icon.is_none())
                             .for_each(|action| action.icon = data.icon.clone());
                         let alias = {
                             let mut aliases = aliases.lock_blocking();
-                            aliases.remove(&data.name)
+                            aliases
+                                .remove(&data.name)
+                                .or_else(|| aliases.remove(default_name))
                         };
                         data.apply_alias(alias);
                         // apply counts
@@ -152,7 +236,7 @@ impl Loader {
                             .as_ref()
                             .and_then(|exec| counts.get(exec))
                             .unwrap_or(&0.0);
-                        let priority = parse_priority(priority, *count, decimals);
+                        let priority = parse_priority(priority, *count);
                         data.priority = priority;
                         Some(data)
                     }
@@ -167,7 +251,6 @@ impl Loader {
         mut apps: HashSet<AppData>,
         priority: f32,
         counts: &HashMap<String, f32>,
-        decimals: i32,
         last_changed: Option<SystemTime>,
     ) -> Result<HashSet<AppData>, SherlockError> {
         let system_apps = get_applications_dir();
@@ -201,7 +284,7 @@ impl Loader {
         });
 
         // get information for uncached applications
-        match Loader::load_applications_from_disk(Some(desktop_files), priority, counts, decimals) {
+        match Loader::load_applications_from_disk(Some(desktop_files), priority, counts) {
             Ok(new_apps) => apps.extend(new_apps),
             _ => {}
         };
@@ -214,9 +297,15 @@ impl Loader {
             let _ = fs::create_dir_all(parent);
         }
         let tmp_path = path.with_extension(".tmp");
+        let cache = AppCache {
+            version: CACHE_VERSION,
+            locales: get_locales(),
+            desktops: get_current_desktops(),
+            apps,
+        };
 
         if let Ok(f) = File::create(&tmp_path) {
-            if let Ok(_) = simd_json::to_writer(f, &apps) {
+            if let Ok(_) = simd_json::to_writer(f, &cache) {
                 let _ = fs::rename(&tmp_path, &cache_loc);
             } else {
                 let _ = fs::remove_file(&tmp_path);
@@ -227,7 +316,6 @@ impl Loader {
     pub fn load_applications(
         priority: f32,
         counts: &HashMap<String, f32>,
-        decimals: i32,
     ) -> Result<HashSet<AppData>, SherlockError> {
         let config = CONFIG
             .get()
@@ -242,21 +330,29 @@ impl Loader {
             || file_has_changed(&config_path, &cache_path);
 
         if !changed {
+            // Caches of an older format, another locale or another desktop get rebuilt
             let cached_apps: Option<HashSet<AppData>> = File::open(&config.behavior.cache)
                 .ok()
-                .and_then(|f| simd_json::from_reader(f).ok());
+                .and_then(|f| simd_json::from_reader::<_, AppCache<HashSet<AppData>>>(f).ok())
+                .filter(|cache| {
+                    cache.version == CACHE_VERSION
+                        && cache.locales == get_locales()
+                        && cache.desktops == get_current_desktops()
+                })
+                .map(|cache| cache.apps);
 
             if let Some(mut apps) = cached_apps {
-                // apply the current counts
+                // apply the current counts and drop apps whose binary has been removed
                 apps = apps
                     .drain()
+                    .filter(|v| v.try_exec.as_deref().map_or(true, binary_exists))
                     .map(|mut v| {
                         let count = v
                             .exec
                             .as_ref()
                             .and_then(|exec| counts.get(exec))
                             .unwrap_or(&0.0);
-                        let new_priority = parse_priority(priority, *count, decimals);
+                        let new_priority = parse_priority(priority, *count);
                         v.priority = new_priority;
                         v
                     })
@@ -272,7 +368,6 @@ impl Loader {
                             old_apps,
                             priority,
                             &counts_clone,
-                            decimals,
                             last_changed,
                         ) {
                             Loader::write_cache(&new_apps, &config.behavior.cache);
@@ -283,7 +378,7 @@ impl Loader {
             }
         }
 
-        let apps = Loader::load_applications_from_disk(None, priority, counts, decimals)?;
+        let apps = Loader::load_applications_from_disk(None, priority, counts)?;
         // Write the cache in the background
         let app_clone = apps.clone();
         rayon::spawn_fifo(move || Loader::write_cache(&app_clone, &config.behavior.cache));
@@ -291,16 +386,128 @@ impl Loader {
     }
 }
 
+/// Keeps the best match of a localized key like `Name[de_DE]`
+#[derive(Default)]
+struct Localized {
+    default: Option<String>,
+    best: Option<(usize, String)>,
+}
+impl Localized {
+    fn offer(&mut self, locale: Option<&str>, value: &str, locales: &[String]) {
+        let Some(locale) = locale else {
+            self.default = Some(value.to_string());
+            return;
+        };
+        if let Some(rank) = locales.iter().position(|l| l == locale) {
+            if self.best.as_ref().map_or(true, |(best, _)| rank < *best) {
+                self.best = Some((rank, value.to_string()));
+            }
+        }
+    }
+    fn value(&self) -> Option<&str> {
+        self.best
+            .as_ref()
+            .map(|(_, value)| value.as_str())
+            .or(self.default.as_deref())
+    }
+}
+
+/// Splits a key like `Name[de_DE]` into `("Name", Some("de_DE"))`
+fn split_locale(key: &str) -> (&str, Option<&str>) {
+    match key.split_once('[') {
+        Some((key, locale)) => (key, locale.strip_suffix(']')),
+        None => (key, None),
+    }
+}
+
+/// Returns the locales localized keys are matched against, ordered from the most to the least
+/// specific as described by the desktop entry specification. `LC_ALL` takes precedence over
+/// `LC_MESSAGES` and `LANG`.
+fn get_locales() -> Vec<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|locale| !locale.is_empty())
+        .map(|locale| locale_candidates(&locale))
+        .unwrap_or_default()
+}
+
+/// Expands `lang_COUNTRY.ENCODING@MODIFIER` into `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`,
+/// `lang@MODIFIER` and `lang`, skipping the parts that are not present
+fn locale_candidates(locale: &str) -> Vec<String> {
+    let (locale, modifier) = match locale.split_once('@') {
+        Some((locale, modifier)) => (locale, Some(modifier)),
+        None => (locale, None),
+    };
+    let locale = locale.split('.').next().unwrap_or(locale);
+    let (lang, country) = match locale.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (locale, None),
+    };
+    if lang.is_empty() || lang == "C" || lang == "POSIX" {
+        return vec![];
+    }
+
+    let mut candidates = Vec::with_capacity(4);
+    if let (Some(country), Some(modifier)) = (country, modifier) {
+        candidates.push(format!("{}_{}@{}", lang, country, modifier));
+    }
+    if let Some(country) = country {
+        candidates.push(format!("{}_{}", lang, country));
+    }
+    if let Some(modifier) = modifier {
+        candidates.push(format!("{}@{}", lang, modifier));
+    }
+    candidates.push(lang.to_string());
+    candidates
+}
+
+/// Checks whether a `TryExec` binary exists, either as an absolute path or inside `$PATH`
+fn binary_exists(binary: &str) -> bool {
+    fn is_executable(path: &Path) -> bool {
+        path.metadata()
+            .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    let path = Path::new(binary);
+    if path.is_absolute() {
+        return is_executable(path);
+    }
+    env::var_os("PATH").map_or(false, |paths| {
+        env::split_paths(&paths).any(|dir| is_executable(&dir.join(binary)))
+    })
+}
+
+/// Returns the lowercased desktop names listed in `XDG_CURRENT_DESKTOP`
+fn get_current_desktops() -> Vec<String> {
+    env::var("XDG_CURRENT_DESKTOP")
+        .map(|desktops| {
+            desktops
+                .split(':')
+                .filter(|d| !d.is_empty())
+                .map(|d| d.to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether any of the `;`-separated desktops in an `OnlyShowIn`/`NotShowIn` value is currently
+/// running
+fn shown_in(value: &str, current_desktops: &[String]) -> bool {
+    value
+        .split(';')
+        .map(|d| d.trim().to_lowercase())
+        .any(|d| !d.is_empty() && current_desktops.contains(&d))
+}
+
 fn should_ignore(ignore_apps: &Vec<Pattern>, app: &str) -> bool {
     let app_name = app.to_lowercase();
     ignore_apps.iter().any(|pattern| pattern.matches(&app_name))
 }
-pub fn parse_priority(priority: f32, count: f32, decimals: i32) -> f32 {
-    if count == 0.0 {
-        priority + 1.0
-    } else {
-        priority + 1.0 - count * 10f32.powi(-decimals)
-    }
+/// Lowers the priority by the frecency score. The score lies within `[0, 1)`, so tiles never
+/// leave the priority band of their launcher.
+pub fn parse_priority(priority: f32, score: f32) -> f32 {
+    priority + 1.0 - score.clamp(0.0, 0.999)
 }
 
 pub fn get_applications_dir() -> HashSet<PathBuf> {
@@ -362,6 +569,17 @@ pub fn file_has_changed(file_path: &Path, compare_to: &Path) -> bool {
     false
 }
 
+#[test]
+fn test_locale_candidates() {
+    assert_eq!(
+        locale_candidates("sr_YU.UTF-8@Latn"),
+        vec!["sr_YU@Latn", "sr_YU", "sr@Latn", "sr"]
+    );
+    assert_eq!(locale_candidates("de_DE.UTF-8"), vec!["de_DE", "de"]);
+    assert_eq!(locale_candidates("fr"), vec!["fr"]);
+    assert!(locale_candidates("C.UTF-8").is_empty());
+}
+
 #[test]
 fn test_get_applications_dir() {
     // Test input path
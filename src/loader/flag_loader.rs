@@ -1,73 +1,86 @@
-use std::{env, path::PathBuf};
+use std::{env, path::Path};
 
 use super::Loader;
 use crate::utils::{
-    config::{SherlockConfig, SherlockFlags},
+    cli::{self, ParsedArgs},
+    config::{PipeBind, SherlockFlags},
     errors::SherlockError,
 };
 
 impl Loader {
     #[sherlock_macro::timing(name = "Loading flags", level = "setup")]
     pub fn load_flags() -> Result<SherlockFlags, SherlockError> {
-        let args: Vec<String> = env::args().collect();
-        if args.contains(&"--help".to_string()) {
-            let _ = print_help();
-            std::process::exit(0);
+        let mut args = env::args();
+        let program = args.next().unwrap_or_default();
+        let mut args: Vec<String> = args.collect();
+        // Allows using Sherlock as a drop-in replacement via a `dmenu` symlink
+        if Path::new(&program)
+            .file_name()
+            .is_some_and(|name| name == "dmenu")
+        {
+            args.insert(0, String::from("--dmenu"));
         }
-        if args.contains(&"-h".to_string()) {
+        let args = cli::parse(args).unwrap_or_else(|error| {
+            eprintln!("sherlock: {}", error);
+            eprintln!("Try 'sherlock --help' for more information.");
+            std::process::exit(2);
+        });
+        if args.switch("--help") {
             let _ = print_help();
             std::process::exit(0);
         }
-        if args.contains(&"--version".to_string()) {
+        if args.switch("--version") {
             let _ = print_version();
             std::process::exit(0);
         }
 
-        SherlockFlags::new(args)
+        let flags = SherlockFlags::new(args).unwrap_or_else(|error| {
+            eprintln!("sherlock: {}", error);
+            std::process::exit(2);
+        });
+        Ok(flags)
     }
 }
 impl SherlockFlags {
-    fn new(args: Vec<String>) -> Result<Self, SherlockError> {
-        // Helper closure to extract flag values
-        let extract_path_value = |flag: &str| {
-            args.iter()
-                .position(|arg| arg == flag)
-                .map_or(None, |index| args.get(index + 1))
-                .map(|s| PathBuf::from(s))
-        };
-        let check_flag_existance = |flag: &str| {
-            args.iter()
-                .position(|arg| arg == flag)
-                .map_or(false, |_| true)
-        };
-        let extract_flag_value = |flag: &str| {
-            args.iter()
-                .position(|arg| arg == flag)
-                .map_or(None, |i| args.get(i + 1))
-                .cloned()
-        };
-
-        if check_flag_existance("init") {
-            let path = extract_path_value("init").unwrap_or(PathBuf::from("~/.config/sherlock/"));
-            let x = SherlockConfig::to_file(path);
-            println!("{:?}", x);
-        }
-
+    fn new(args: ParsedArgs) -> Result<Self, String> {
+        let binds = args
+            .texts("--bind")
+            .iter()
+            .map(|spec| {
+                PipeBind::parse(spec).ok_or_else(|| {
+                    format!(
+                        "Invalid binding '{}'. Expected KEY=ACTION[:EXIT_CODE]",
+                        spec
+                    )
+                })
+            })
+            .collect::<Result<Vec<PipeBind>, String>>()?;
         Ok(SherlockFlags {
-            config: extract_path_value("--config"),
-            fallback: extract_path_value("--fallback"),
-            style: extract_path_value("--style"),
-            ignore: extract_path_value("--ignore"),
-            alias: extract_path_value("--alias"),
-            display_raw: check_flag_existance("--display-raw"),
-            center_raw: check_flag_existance("--center"),
-            cache: extract_path_value("--cache"),
-            daemonize: check_flag_existance("--daemonize"),
-            sub_menu: extract_flag_value("--sub-menu"),
-            method: extract_flag_value("--method"),
-            field: extract_flag_value("--field"),
-            multi: check_flag_existance("--multi"),
-            photo_mode: check_flag_existance("--photo"),
+            config: args.path("--config"),
+            fallback: args.path("--fallback"),
+            style: args.path("--style"),
+            ignore: args.path("--ignore"),
+            alias: args.path("--alias"),
+            display_raw: args.switch("--display-raw"),
+            center_raw: args.switch("--center"),
+            cache: args.path("--cache"),
+            daemonize: args.switch("--daemonize"),
+            sub_menu: args.text("--sub-menu"),
+            method: args.text("--method"),
+            field: args.text("--field"),
+            multi: args.switch("--multi"),
+            output_format: args.text("--output-format"),
+            preview_cmd: args.text("--preview-cmd"),
+            photo_mode: args.switch("--photo"),
+            socket: args.path("--socket"),
+            dmenu: args.dmenu(),
+            prompt: args.text("-p"),
+            case_insensitive: args.switch("-i"),
+            lines: args.number("-l"),
+            format: args.text("-format"),
+            selected_row: args.number("-selected-row"),
+            binds,
+            command: args.command,
         })
     }
 }
@@ -80,55 +93,9 @@ pub fn print_version() -> Result<(), SherlockError> {
     Ok(())
 }
 pub fn print_help() -> Result<(), SherlockError> {
-    let allowed_flags: Vec<(&str, &str)> = vec![
-        ("\nBASICS:", ""),
-        ("--version", "Print the version of the application."),
-        ("--help", "Show this help message with allowed flags."),
-        ("init", "Writes default configs into your config directory."),
-        ("\nFILES:", ""),
-        ("--config", "Specify the configuration file to load."),
-        ("--fallback", "Specify the fallback file to load."),
-        ("--style", "Set the style configuration file."),
-        ("--ignore", "Specify the sherlock ignore file"),
-        ("--alias", "Specify the sherlock alias file (.json)."),
-        ("--cache", "Specify the sherlock cache file (.json)."),
-        ("\nBEHAVIOR:", ""),
-        (
-            "--daemonize",
-            "If this flag is set, sherlock will run in daemon mode.",
-        ),
-        (
-            "--sub-menu",
-            "Start sherlock with an alias active already. For example 'pm' for power menu",
-        ),
-        (
-            "--time-inspect",
-            "Show time for loading launchers and from 0 to full content",
-        ),
-        ("\nPIPE MODE:", ""),
-        (
-            "--display-raw",
-            "Force Sherlock to use a singular tile to display the piped content",
-        ),
-        (
-            "--method",
-            "Specifies what to do with the selected data row",
-        ),
-        (
-            "--field",
-            "Specifies which of your fields should be printed on return press",
-        ),
-    ];
-
-    // Print header
-    println!("{:<15} {}", "Flag", "Description");
-
-    for (flag, explanation) in allowed_flags {
-        println!("{:<15} {}", flag, explanation);
-    }
-
+    println!("{}", cli::help());
     println!(
-        "\n\nFor more help:\nhttps://github.com/Skxxtz/sherlock/blob/documentation/docs/flags.md\n\n"
+        "\nFor more help:\nhttps://github.com/Skxxtz/sherlock/blob/documentation/docs/flags.md\n\n"
     );
 
     Ok(())
@@ -52,28 +52,24 @@ impl Loader {
 
         // Read cached counter file
         let counter_reader = CounterReader::new()?;
-        let counts: HashMap<String, f32> =
-            JsonCache::read(&counter_reader.path).unwrap_or_default();
-        let max_decimals = counts
-            .iter()
-            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
-            .map(|(_, v)| v.to_string().len())
-            .unwrap_or(0) as i32;
+        let counts: HashMap<String, f32> = counter_reader
+            .scores(config.behavior.frecency_half_life)
+            .unwrap_or_default();
 
         // Parse the launchers
         let deserialized_launchers: Vec<Result<Launcher, SherlockError>> = raw_launchers
             .into_iter()
             .map(|raw| {
                 let launcher_type: LauncherType = match raw.r#type.as_str() {
-                    "app_launcher" => parse_app_launcher(&raw, &counts, max_decimals),
+                    "app_launcher" => parse_app_launcher(&raw, &counts),
                     "audio_sink" => parse_audio_sink_launcher(),
                     "bookmarks" => parse_bookmarks_launcher(&raw),
                     "bulk_text" => parse_bulk_text_launcher(&raw),
                     "calculation" => parse_calculator(&raw),
-                    "categories" => parse_category_launcher(&raw, &counts, max_decimals),
+                    "categories" => parse_category_launcher(&raw, &counts),
                     "clipboard-execution" => parse_clipboard_launcher(&raw)?,
-                    "command" => parse_command_launcher(&raw, &counts, max_decimals),
-                    "debug" => parse_debug_launcher(&raw, &counts, max_decimals),
+                    "command" => parse_command_launcher(&raw, &counts),
+                    "debug" => parse_debug_launcher(&raw, &counts),
                     "emoji_picker" => parse_emoji_launcher(&raw),
                     "files" => parse_file_launcher(&raw),
                     "teams_event" => parse_event_launcher(&raw),
@@ -105,10 +101,10 @@ impl Loader {
         let mut non_breaking: Vec<SherlockError> =
             errs.into_iter().filter_map(Result::err).collect();
         if counts.is_empty() {
-            let counts: HashMap<String, u32> = launchers
+            let counts: HashMap<String, Vec<u64>> = launchers
                 .iter()
                 .filter_map(|launcher| launcher.get_execs())
-                .flat_map(|exec_set| exec_set.into_iter().map(|exec| (exec, 0)))
+                .flat_map(|exec_set| exec_set.into_iter().map(|exec| (exec, vec![])))
                 .collect();
             if let Err(e) = JsonCache::write(&counter_reader.path, &counts) {
                 non_breaking.push(e)
@@ -118,12 +114,7 @@ impl Loader {
         Ok((launchers, non_breaking))
     }
 }
-fn parse_appdata(
-    value: &Value,
-    prio: f32,
-    counts: &HashMap<String, f32>,
-    max_decimals: i32,
-) -> HashSet<AppData> {
+fn parse_appdata(value: &Value, prio: f32, counts: &HashMap<String, f32>) -> HashSet<AppData> {
     let data: HashSet<AppData> =
         deserialize_named_appdata(value.clone().into_deserializer()).unwrap_or_default();
     data.into_iter()
@@ -133,24 +124,21 @@ fn parse_appdata(
                 .as_ref()
                 .and_then(|exec| counts.get(exec))
                 .unwrap_or(&0.0);
-            c.with_priority(parse_priority(prio, *count, max_decimals))
+            c.with_priority(parse_priority(prio, *count))
         })
         .collect::<HashSet<AppData>>()
 }
 #[sherlock_macro::timing(level = "launchers")]
-fn parse_app_launcher(
-    raw: &RawLauncher,
-    counts: &HashMap<String, f32>,
-    max_decimals: i32,
-) -> LauncherType {
+fn parse_app_launcher(raw: &RawLauncher, counts: &HashMap<String, f32>) -> LauncherType {
     let apps: HashSet<AppData> = CONFIG.get().map_or_else(
         || HashSet::new(),
         |config| {
             let prio = raw.priority;
             match config.behavior.caching {
-                true => Loader::load_applications(prio, counts, max_decimals).unwrap_or_default(),
-                false => Loader::load_applications_from_disk(None, prio, counts, max_decimals)
-                    .unwrap_or_default(),
+                true => Loader::load_applications(prio, counts).unwrap_or_default(),
+                false => {
+                    Loader::load_applications_from_disk(None, prio, counts).unwrap_or_default()
+                }
             }
         },
     );
@@ -233,14 +221,10 @@ fn parse_calculator(raw: &RawLauncher) -> LauncherType {
     LauncherType::Calc(CalculatorLauncher { capabilities })
 }
 #[sherlock_macro::timing(level = "launchers")]
-fn parse_category_launcher(
-    raw: &RawLauncher,
-    counts: &HashMap<String, f32>,
-    max_decimals: i32,
-) -> LauncherType {
+fn parse_category_launcher(raw: &RawLauncher, counts: &HashMap<String, f32>) -> LauncherType {
     let prio = raw.priority;
     let value = &raw.args["categories"];
-    let categories = parse_appdata(value, prio, counts, max_decimals);
+    let categories = parse_appdata(value, prio, counts);
     LauncherType::Category(CategoryLauncher { categories })
 }
 #[sherlock_macro::timing(level = "launchers")]
@@ -286,25 +270,17 @@ fn parse_clipboard_launcher(raw: &RawLauncher) -> Result<LauncherType, SherlockE
     }
 }
 #[sherlock_macro::timing(level = "launchers")]
-fn parse_command_launcher(
-    raw: &RawLauncher,
-    counts: &HashMap<String, f32>,
-    max_decimals: i32,
-) -> LauncherType {
+fn parse_command_launcher(raw: &RawLauncher, counts: &HashMap<String, f32>) -> LauncherType {
     let prio = raw.priority;
     let value = &raw.args["commands"];
-    let commands = parse_appdata(value, prio, counts, max_decimals);
+    let commands = parse_appdata(value, prio, counts);
     LauncherType::Command(CommandLauncher { commands })
 }
 #[sherlock_macro::timing(level = "launchers")]
-fn parse_debug_launcher(
-    raw: &RawLauncher,
-    counts: &HashMap<String, f32>,
-    max_decimals: i32,
-) -> LauncherType {
+fn parse_debug_launcher(raw: &RawLauncher, counts: &HashMap<String, f32>) -> LauncherType {
     let prio = raw.priority;
     let value = &raw.args["commands"];
-    let commands = parse_appdata(value, prio, counts, max_decimals);
+    let commands = parse_appdata(value, prio, counts);
     LauncherType::Command(CommandLauncher { commands })
 }
 #[sherlock_macro::timing(level = "launchers")]
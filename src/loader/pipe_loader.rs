@@ -1,47 +1,92 @@
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::{self, BufRead};
 use std::os::linux::fs::MetadataExt;
+use std::thread;
 
 use gtk4::gdk::Display;
 use gtk4::IconTheme;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use simd_json::base::ValueTryAsMutObject;
 use simd_json::OwnedValue;
 
 use crate::api::call::ApiCall;
+use crate::utils::config::default_true;
 use crate::CONFIG;
 
+use super::util::ApplicationAction;
 use super::Loader;
 
 impl Loader {
-    pub fn load_pipe_args() -> Vec<u8> {
+    /// Reads stdin on a background thread while it is still open. Every received chunk
+    /// consists of complete lines. Returns `None` if stdin is a terminal.
+    pub fn stream_pipe_args() -> Option<async_channel::Receiver<Vec<u8>>> {
         if let Ok(metadata) = File::open("/dev/stdin").and_then(|f| f.metadata()) {
             // 0o020000 - Character device (e.g. TTY)
             // 0o170000 - octal mask to extract all file types
             if metadata.st_mode() & 0o170000 == 0o020000 {
-                return vec![];
+                return None;
             }
         }
-        let stdin = io::stdin();
-        let mut buf = Vec::new();
-        let _ = stdin.lock().read_to_end(&mut buf);
-        return buf;
+        let (sender, receiver) = async_channel::unbounded();
+        thread::spawn(move || {
+            let mut stdin = io::stdin().lock();
+            let mut partial: Vec<u8> = Vec::new();
+            loop {
+                let buf = match stdin.fill_buf() {
+                    Ok(buf) if !buf.is_empty() => buf,
+                    _ => break,
+                };
+                let len = buf.len();
+                // Send everything up to the last newline, the rest waits for the next read
+                match buf.iter().rposition(|&b| b == b'\n') {
+                    Some(pos) => {
+                        partial.extend_from_slice(&buf[..=pos]);
+                        let chunk = std::mem::replace(&mut partial, buf[pos + 1..].to_vec());
+                        if sender.send_blocking(chunk).is_err() {
+                            return;
+                        }
+                    }
+                    None => partial.extend_from_slice(buf),
+                }
+                stdin.consume(len);
+            }
+            if !partial.is_empty() {
+                let _ = sender.send_blocking(partial);
+            }
+        });
+        Some(receiver)
+    }
+    /// Waits until stdin is closed and returns all of its content
+    pub async fn collect_pipe_args(
+        mut buf: Vec<u8>,
+        receiver: &async_channel::Receiver<Vec<u8>>,
+    ) -> Vec<u8> {
+        while let Ok(chunk) = receiver.recv().await {
+            buf.extend(chunk);
+        }
+        buf
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PipedElements {
     pub title: Option<String>,
     pub description: Option<String>,
     pub icon: Option<String>,
     pub icon_size: Option<i32>,
     pub result: Option<String>,
+    #[serde(skip_serializing)]
     pub binary: Option<Vec<u8>>,
     pub method: Option<String>,
     pub field: Option<String>,
     pub hidden: Option<HashMap<String, String>>,
+    #[serde(default = "default_true")]
     pub exit: bool,
+    #[serde(default, skip_serializing)]
+    pub actions: Option<Vec<ApplicationAction>>,
+    #[serde(default, skip_serializing)]
+    pub preview: Option<String>,
 }
 impl PipedElements {
     pub fn clean(&mut self) {
@@ -67,6 +112,44 @@ impl PipedElements {
             self.result = Some(cleaned);
         }
     }
+    /// Formats the element for `--output-format` or dmenu's `-format`. `index` is the line of
+    /// the element in the piped input and `trigger` names the key or action that selected it.
+    pub fn output(&self, format: &str, index: usize, trigger: &str) -> Option<String> {
+        match format {
+            "index" | "i" => Some(index.to_string()),
+            // Dmenu's `-format d` counts from 1
+            "d" => Some((index + 1).to_string()),
+            "json" => {
+                let mut value = serde_json::to_value(self).ok()?;
+                let obj = value.as_object_mut()?;
+                obj.insert(String::from("index"), index.into());
+                obj.insert(String::from("trigger"), trigger.into());
+                serde_json::to_string(&value).ok()
+            }
+            "tsv" => {
+                let mut hidden: Vec<(&String, &String)> = self.hidden.iter().flatten().collect();
+                hidden.sort();
+                let columns = [
+                    index.to_string(),
+                    trigger.to_string(),
+                    self.title.clone().unwrap_or_default(),
+                    self.description.clone().unwrap_or_default(),
+                    self.result.clone().unwrap_or_default(),
+                ]
+                .into_iter()
+                .chain(hidden.into_iter().map(|(k, v)| format!("{}={}", k, v)))
+                .map(|column| {
+                    column
+                        .replace('\\', r"\\")
+                        .replace('\t', r"\t")
+                        .replace('\n', r"\n")
+                })
+                .collect::<Vec<String>>();
+                Some(columns.join("\t"))
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -101,6 +184,17 @@ impl PipedData {
             })
         }
     }
+    /// Whether the input can be displayed before stdin is closed. This is the case for plain
+    /// lines and for NDJSON elements, but not for a single JSON document.
+    pub fn is_streamable<T: AsRef<[u8]>>(first: T) -> bool {
+        let text = String::from_utf8_lossy(first.as_ref());
+        let line = text.trim_start().lines().next().unwrap_or_default();
+        if !line.starts_with('{') {
+            return true;
+        }
+        serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(line)
+            .is_ok_and(|obj| !obj.contains_key("elements") && !obj.contains_key("settings"))
+    }
     pub fn elements<T: AsRef<[u8]>>(msg: T) -> Option<Vec<PipedElements>> {
         let mut buf = msg.as_ref().to_vec();
 
@@ -120,6 +214,8 @@ impl PipedData {
         }
         Some(elements)
     }
+    /// Parses piped lines. A line can either be plain text or, outside of dmenu mode, a JSON
+    /// element (NDJSON).
     pub fn deserialize_pipe<T: AsRef<[u8]>>(buf: T) -> Option<Vec<PipedElements>> {
         let buf = buf.as_ref().to_vec();
 
@@ -138,7 +234,23 @@ impl PipedData {
             let chunk = &buf[start..end];
 
             // Check if the chunk contains valid UTF-8
-            if let Ok(line) = std::str::from_utf8(chunk) {
+            let line =
+                std::str::from_utf8(chunk).map(|line| line.strip_suffix('\n').unwrap_or(line));
+            let element = line
+                .ok()
+                .filter(|line| !config.runtime.dmenu && line.starts_with('{'))
+                .and_then(|line| serde_json::from_str::<PipedElements>(line).ok());
+
+            if let Some(mut element) = element {
+                element.clean();
+                if element.method.is_none() {
+                    element.method = config.runtime.method.clone();
+                }
+                if element.field.is_none() {
+                    element.field = config.behavior.field.clone();
+                }
+                result.push(element);
+            } else if let Ok(line) = line {
                 // Treat it as a normal string (text line)
                 let mut raw_meta: Vec<&str> = line.split('\0').collect();
                 let name = raw_meta.remove(0).to_string();
@@ -174,6 +286,8 @@ impl PipedData {
                     field: config.behavior.field.clone(),
                     hidden: None,
                     exit: true,
+                    actions: None,
+                    preview: None,
                 });
             } else {
                 // If it's not valid UTF-8, treat it as binary data
@@ -188,6 +302,8 @@ impl PipedData {
                     method: None,
                     hidden: None,
                     exit: true,
+                    actions: None,
+                    preview: None,
                 });
             }
 
@@ -197,3 +313,68 @@ impl PipedData {
         Some(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_selected_elements() {
+        let element = PipedElements {
+            title: Some(String::from("Fire\tfox")),
+            description: None,
+            icon: None,
+            icon_size: None,
+            result: Some(String::from("firefox")),
+            binary: Some(vec![1, 2, 3]),
+            method: Some(String::from("print")),
+            field: None,
+            hidden: Some(HashMap::from([
+                (String::from("pid"), String::from("42")),
+                (String::from("cmd"), String::from("ff")),
+            ])),
+            exit: true,
+            actions: None,
+            preview: None,
+        };
+
+        assert_eq!(element.output("index", 3, "return").as_deref(), Some("3"));
+        assert_eq!(element.output("i", 3, "return").as_deref(), Some("3"));
+        assert_eq!(element.output("d", 3, "return").as_deref(), Some("4"));
+        assert!(element.output("s", 3, "return").is_none());
+        assert_eq!(
+            element.output("tsv", 3, "click").as_deref(),
+            Some("3\tclick\tFire\\tfox\t\tfirefox\tcmd=ff\tpid=42")
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&element.output("json", 3, "return").unwrap()).unwrap();
+        assert_eq!(json["index"], 3);
+        assert_eq!(json["trigger"], "return");
+        assert_eq!(json["result"], "firefox");
+        assert_eq!(json["hidden"]["pid"], "42");
+        assert!(json.get("binary").is_none());
+
+        assert!(element.output("yaml", 3, "return").is_none());
+    }
+
+    #[test]
+    fn parses_element_actions() {
+        let element: PipedElements = serde_json::from_str(
+            r#"{"title": "notes.md", "actions": [
+                {"name": "Delete", "icon": "edit-delete", "method": "print", "exec": "delete"},
+                {"name": "Preview", "method": "print", "exec": "preview", "exit": false}
+            ]}"#,
+        )
+        .unwrap();
+        let actions = element.actions.as_ref().unwrap();
+        assert_eq!(actions.len(), 2);
+        assert!(actions[0].exit);
+        assert!(!actions[1].exit);
+        assert_eq!(actions[1].exec.as_deref(), Some("preview"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&element.output("json", 0, "Delete").unwrap()).unwrap();
+        assert!(json.get("actions").is_none());
+    }
+}
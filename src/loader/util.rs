@@ -1,3 +1,4 @@
+use once_cell::sync::Lazy;
 use serde::{
     de::{DeserializeOwned, MapAccess, Visitor},
     Deserialize, Deserializer, Serialize,
@@ -5,12 +6,14 @@ use serde::{
 use serde_json::Value;
 use std::{
     borrow::Cow,
-    collections::{BTreeSet, HashMap, HashSet},
+    collections::{HashMap, HashSet},
     env,
     fmt::Debug,
     fs::{self, File},
     hash::{Hash, Hasher},
     path::PathBuf,
+    sync::RwLock,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use crate::{
@@ -89,6 +92,8 @@ pub struct AppData {
     pub exec: Option<String>,
     pub search_string: String,
     #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(default)]
     pub priority: f32,
     pub icon: Option<String>,
     pub icon_class: Option<String>,
@@ -98,7 +103,17 @@ pub struct AppData {
     #[serde(default)]
     pub actions: Vec<ApplicationAction>,
     #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
     pub terminal: bool,
+    #[serde(default)]
+    pub try_exec: Option<String>,
+    #[serde(default)]
+    pub dbus_activatable: bool,
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    #[serde(default)]
+    pub wm_class: Option<String>,
 }
 impl AppData {
     pub fn new() -> Self {
@@ -106,6 +121,7 @@ impl AppData {
             name: String::new(),
             exec: None,
             search_string: String::new(),
+            comment: None,
             priority: 0.0,
             icon: None,
             icon_class: None,
@@ -113,7 +129,12 @@ impl AppData {
             tag_end: None,
             desktop_file: None,
             actions: vec![],
+            categories: vec![],
             terminal: false,
+            try_exec: None,
+            dbus_activatable: false,
+            working_dir: None,
+            wm_class: None,
         }
     }
     pub fn new_for_theme<'a, T, S>(name: T, path: Option<S>, priority: f32) -> Self
@@ -128,6 +149,7 @@ impl AppData {
             name: name_string.clone(),
             exec: path,
             search_string: name_string,
+            comment: None,
             priority,
             icon: Some(String::from("sherlock-devtools")),
             icon_class: None,
@@ -135,7 +157,12 @@ impl AppData {
             tag_end: None,
             desktop_file: None,
             actions: vec![],
+            categories: vec![],
             terminal: false,
+            try_exec: None,
+            dbus_activatable: false,
+            working_dir: None,
+            wm_class: None,
         }
     }
     pub fn from_raw_launcher(raw: &RawLauncher) -> Self {
@@ -170,6 +197,13 @@ impl AppData {
         self.priority = priority;
         self
     }
+    /// The desktop file id used as the D-Bus name of `DBusActivatable` applications
+    pub fn app_id(&self) -> Option<String> {
+        self.desktop_file
+            .as_ref()
+            .and_then(|file| file.file_stem())
+            .map(|stem| stem.to_string_lossy().to_string())
+    }
     pub fn apply_alias(&mut self, alias: Option<SherlockAlias>) {
         if let Some(alias) = alias {
             if let Some(alias_name) = alias.name.as_ref() {
@@ -185,6 +219,8 @@ impl AppData {
             }
             if let Some(alias_exec) = alias.exec.as_ref() {
                 self.exec = Some(alias_exec.to_string());
+                // A custom exec takes precedence over D-Bus activation
+                self.dbus_activatable = false;
             }
             if let Some(add_actions) = alias.add_actions {
                 add_actions.into_iter().for_each(|mut a| {
@@ -256,6 +292,18 @@ pub struct SherlockAlias {
     pub add_actions: Option<Vec<ApplicationAction>>,
 }
 
+/// Maximum number of launches remembered per key
+const MAX_HISTORY: usize = 64;
+
+/// An entry of `counts.json`. Older versions stored a bare counter per key, which is
+/// migrated into launches happening at the time it is read.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum CounterEntry {
+    History(Vec<u64>),
+    Legacy(f64),
+}
+
 pub struct CounterReader {
     pub path: PathBuf,
 }
@@ -279,28 +327,184 @@ impl CounterReader {
         }
         Ok(CounterReader { path })
     }
+    /// Reads the launch history (unix timestamps in seconds) of every key. Legacy counters are
+    /// written back once migrated, so that their launches keep aging from then on.
+    pub fn read(&self) -> Result<HashMap<String, Vec<u64>>, SherlockError> {
+        let content: HashMap<String, CounterEntry> = JsonCache::read(&self.path)?;
+        let (history, migrated) = migrate_counts(content, unix_now());
+        if migrated {
+            JsonCache::write(&self.path, &history)?;
+        }
+        Ok(history)
+    }
     pub fn increment(&self, key: &str) -> Result<(), SherlockError> {
-        let mut content: HashMap<String, u32> = JsonCache::read(&self.path)?;
-        let unique_values: HashMap<u32, u32> = content
-            .values()
-            .copied()
-            .collect::<BTreeSet<_>>()
+        let mut content = self.read().unwrap_or_default();
+        let history = content.entry(key.to_string()).or_default();
+        history.push(unix_now());
+        if history.len() > MAX_HISTORY {
+            history.drain(..history.len() - MAX_HISTORY);
+        }
+        JsonCache::write(&self.path, &content)?;
+        Ok(())
+    }
+    /// Computes the frecency score of every key. Each launch contributes `0.5^(age / half_life)`
+    /// with `half_life` given in days; a non-positive half-life disables the decay.
+    ///
+    /// The scores are normalized into `[0, 1)` so they can be subtracted from a launcher's
+    /// priority without moving the tile out of its launcher's priority band.
+    pub fn scores(&self, half_life: f32) -> Result<HashMap<String, f32>, SherlockError> {
+        let now = unix_now();
+        let half_life = half_life as f64 * 24.0 * 60.0 * 60.0;
+        let raw: HashMap<String, f64> = self
+            .read()?
             .into_iter()
-            .enumerate()
-            .map(|(i, v)| (v, (i + 1) as u32))
+            .map(|(key, history)| (key, decayed_count(&history, now, half_life)))
             .collect();
-
-        content.iter_mut().for_each(|(_, v)| {
-            if let Some(new) = unique_values.get(v) {
-                *v = new.clone();
+        let max = raw.values().copied().fold(0.0, f64::max);
+        Ok(raw
+            .into_iter()
+            .map(|(key, score)| (key, (score / (max + 1.0)) as f32))
+            .collect())
+    }
+}
+/// Turns legacy counters into launches happening at `now`. Returns whether anything was
+/// migrated.
+fn migrate_counts(
+    content: HashMap<String, CounterEntry>,
+    now: u64,
+) -> (HashMap<String, Vec<u64>>, bool) {
+    let mut migrated = false;
+    let history = content
+        .into_iter()
+        .map(|(key, entry)| {
+            let history = match entry {
+                CounterEntry::History(history) => history,
+                CounterEntry::Legacy(count) => {
+                    migrated = true;
+                    vec![now; (count.max(0.0) as usize).min(MAX_HISTORY)]
+                }
+            };
+            (key, history)
+        })
+        .collect();
+    (history, migrated)
+}
+/// Sums up `launches`, each weighted by `0.5^(age / half_life)`. `half_life` is given in
+/// seconds; a non-positive half-life counts every launch fully.
+fn decayed_count(launches: &[u64], now: u64, half_life: f64) -> f64 {
+    launches
+        .iter()
+        .map(|launch| {
+            if half_life <= 0.0 {
+                return 1.0;
             }
-        });
+            let age = now.saturating_sub(*launch) as f64;
+            0.5f64.powf(age / half_life)
+        })
+        .sum()
+}
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
 
-        *content.entry(key.to_string()).or_insert(0) += 1;
-        JsonCache::write(&self.path, &content)?;
-        Ok(())
+/// Length of the longest query prefix that selections are learned for
+const MAX_PREFIX_LEN: usize = 12;
+/// Number of remembered prefixes. The least recently used ones are dropped first.
+const MAX_SELECTIONS: usize = 512;
+
+/// The rows activated after typing a query prefix
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct Selections {
+    keys: HashMap<String, u32>,
+    last_used: u64,
+}
+
+/// Rows activated for a given query prefix, stored as `prefix -> selections`
+pub static SELECTIONS: Lazy<RwLock<HashMap<String, Selections>>> = Lazy::new(|| {
+    let selections = SelectionReader::new().and_then(|reader| JsonCache::read(&reader.path));
+    RwLock::new(selections.unwrap_or_default())
+});
+
+/// Remembers which row was activated for a search query, so that the same row can be
+/// preferred the next time the query, or the beginning of it, is typed. Stored next to the
+/// counter cache.
+pub struct SelectionReader {
+    pub path: PathBuf,
+}
+impl SelectionReader {
+    pub fn new() -> Result<Self, SherlockError> {
+        let counter = CounterReader::new()?;
+        Ok(SelectionReader {
+            path: counter.path.with_file_name("selections.json"),
+        })
+    }
+    pub fn record(&self, query: &str, key: &str) -> Result<(), SherlockError> {
+        let mut selections = SELECTIONS.write().unwrap();
+        record_selection(&mut selections, query, key, unix_now());
+        JsonCache::write(&self.path, &*selections)
+    }
+    /// Returns how often `key` was activated for the longest known prefix of `query`
+    pub fn lookup(query: &str, key: &str) -> u32 {
+        lookup_selection(&SELECTIONS.read().unwrap(), query, key)
+    }
+    pub fn reset(&self) -> Result<(), SherlockError> {
+        SELECTIONS.write().unwrap().clear();
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(sherlock_error!(
+                SherlockErrorType::FileRemoveError(self.path.clone()),
+                e.to_string()
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+/// The lowercase prefixes of `query`, shortest first and up to [`MAX_PREFIX_LEN`] characters
+fn query_prefixes(query: &str) -> Vec<String> {
+    let query = query.trim().to_lowercase();
+    let mut prefixes: Vec<String> = query
+        .char_indices()
+        .take(MAX_PREFIX_LEN)
+        .map(|(i, c)| query[..i + c.len_utf8()].trim_end().to_string())
+        .collect();
+    // "a b" and "a " share the prefix "a"
+    prefixes.dedup();
+    prefixes
+}
+fn record_selection(
+    selections: &mut HashMap<String, Selections>,
+    query: &str,
+    key: &str,
+    now: u64,
+) {
+    for prefix in query_prefixes(query) {
+        let selection = selections.entry(prefix).or_default();
+        *selection.keys.entry(key.to_string()).or_insert(0) += 1;
+        selection.last_used = now;
+    }
+    if selections.len() > MAX_SELECTIONS {
+        let mut by_age: Vec<(u64, String)> = selections
+            .iter()
+            .map(|(prefix, selection)| (selection.last_used, prefix.clone()))
+            .collect();
+        by_age.sort_unstable();
+        let excess = selections.len() - MAX_SELECTIONS;
+        by_age.into_iter().take(excess).for_each(|(_, prefix)| {
+            selections.remove(&prefix);
+        });
     }
 }
+fn lookup_selection(selections: &HashMap<String, Selections>, query: &str, key: &str) -> u32 {
+    query_prefixes(query)
+        .iter()
+        .rev()
+        .find_map(|prefix| selections.get(prefix))
+        .and_then(|selection| selection.keys.get(key))
+        .copied()
+        .unwrap_or(0)
+}
 
 pub struct JsonCache;
 impl JsonCache {
@@ -335,3 +539,86 @@ impl JsonCache {
             .map_err(|e| sherlock_error!(SherlockErrorType::DeserializationError, e.to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    #[test]
+    fn decays_launches_by_half_life() {
+        let now = 100 * DAY;
+        let half_life = DAY as f64;
+        assert_eq!(decayed_count(&[now], now, half_life), 1.0);
+        assert_eq!(decayed_count(&[now - DAY], now, half_life), 0.5);
+        assert_eq!(
+            decayed_count(&[now - 2 * DAY, now - DAY], now, half_life),
+            0.75
+        );
+        // Launches in the future, e.g. after a clock change, count fully
+        assert_eq!(decayed_count(&[now + DAY], now, half_life), 1.0);
+        assert_eq!(decayed_count(&[0, now - DAY, now], now, 0.0), 3.0);
+    }
+
+    #[test]
+    fn migrates_legacy_counts_once() {
+        let path =
+            env::temp_dir().join(format!("sherlock-test-counts-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{"firefox": 3, "kitty": [100, 200], "huge": 1000}"#,
+        )
+        .unwrap();
+        let reader = CounterReader { path: path.clone() };
+
+        let history = reader.read().unwrap();
+        assert_eq!(history["kitty"], vec![100, 200]);
+        assert_eq!(history["firefox"].len(), 3);
+        assert!(history["firefox"]
+            .iter()
+            .all(|t| *t == history["firefox"][0]));
+        assert_eq!(history["huge"].len(), MAX_HISTORY);
+
+        // The migrated launches are stored and keep their time on the next start
+        let stored: HashMap<String, Vec<u64>> = JsonCache::read(&path).unwrap();
+        assert_eq!(stored, history);
+        assert_eq!(reader.read().unwrap(), history);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn learns_selections_by_prefix() {
+        let mut selections = HashMap::new();
+        record_selection(&mut selections, "Fir", "firefox", 1);
+        record_selection(&mut selections, "fil", "files", 2);
+
+        assert_eq!(lookup_selection(&selections, "fir", "firefox"), 1);
+        assert_eq!(lookup_selection(&selections, "fi", "firefox"), 1);
+        assert_eq!(lookup_selection(&selections, "fi", "files"), 1);
+        // Longer queries fall back to the longest known prefix
+        assert_eq!(lookup_selection(&selections, "fire", "firefox"), 1);
+        assert_eq!(lookup_selection(&selections, "fire", "files"), 0);
+        assert_eq!(lookup_selection(&selections, "kitty", "firefox"), 0);
+        assert_eq!(lookup_selection(&selections, " ", "firefox"), 0);
+
+        record_selection(&mut selections, "vs code", "code", 3);
+        assert_eq!(selections["vs"].keys["code"], 1);
+        assert_eq!(lookup_selection(&selections, "vs  co", "code"), 1);
+    }
+
+    #[test]
+    fn drops_the_oldest_selections() {
+        let mut selections = HashMap::new();
+        record_selection(&mut selections, &"a".repeat(MAX_PREFIX_LEN + 5), "long", 1);
+        assert_eq!(selections.len(), MAX_PREFIX_LEN);
+
+        for i in 0..MAX_SELECTIONS {
+            record_selection(&mut selections, &format!("q{}", i), "key", 10 + i as u64);
+        }
+        assert_eq!(selections.len(), MAX_SELECTIONS);
+        assert!(!selections.contains_key("a"));
+        assert!(selections.contains_key("q"));
+        assert_eq!(selections["q"].keys["key"], MAX_SELECTIONS as u32);
+    }
+}
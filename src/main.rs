@@ -27,15 +27,18 @@ use api::server::SherlockServer;
 use application::lock::{self, LockFile};
 use loader::Loader;
 use utils::{
-    config::SherlockConfig,
+    config::{ConfigCell, SherlockConfig},
     errors::{SherlockError, SherlockErrorType},
+    files::RuntimePaths,
 };
 
-const SOCKET_PATH: &str = "/tmp/sherlock_daemon.socket";
-const SOCKET_DIR: &str = "/tmp/";
-const LOCK_FILE: &str = "/tmp/sherlock.lock";
+static CONFIG: ConfigCell = ConfigCell::new();
+static RUNTIME_PATHS: OnceLock<RuntimePaths> = OnceLock::new();
 
-static CONFIG: OnceLock<SherlockConfig> = OnceLock::new();
+/// Returns the socket and lock file locations resolved during startup
+fn runtime_paths() -> &'static RuntimePaths {
+    RUNTIME_PATHS.get_or_init(|| RuntimePaths::new(None).unwrap_or_default())
+}
 
 #[tokio::main]
 async fn main() {
@@ -43,6 +46,7 @@ async fn main() {
     let (application, startup_errors, non_breaking, sherlock_flags, app_config, lock) =
         startup_loading().await;
     let t01 = Instant::now();
+    let dmenu = sherlock_flags.dmenu;
     application.connect_activate(move |app| {
         let sherlock = Rc::new(RefCell::new(api::api::SherlockAPI::new(app)));
         let t1 = Instant::now();
@@ -60,6 +64,7 @@ async fn main() {
         {
             let mut sherlock = sherlock.borrow_mut();
             sherlock.window = Some(window.downgrade());
+            sherlock.flags = Some(sherlock_flags.clone());
             sherlock.open_window = Some(open_win.clone());
             sherlock.stack = Some(stack.downgrade());
         }
@@ -139,31 +144,16 @@ async fn main() {
                 } else {
                     false
                 };
+                let stream = Loader::stream_pipe_args();
                 {
                     let mut sherlock = sherlock.borrow_mut();
-                    let pipe = Loader::load_pipe_args();
-                    let mut mode: Option<SherlockModes> = None;
-                    if !pipe.is_empty() {
-                        if sherlock_flags.display_raw {
-                            let pipe = String::from_utf8_lossy(&pipe).to_string();
-                            mode = Some(SherlockModes::DisplayRaw(pipe));
-                        } else if let Some(mut data) = PipedData::new(&pipe){
-                            if let Some(settings) = data.settings.take(){
-                                settings.into_iter().for_each(|request| {
-                                    sherlock.await_request(request);
-                                });
-                            }
-                            mode = data.elements.take().map(|elements| SherlockModes::Pipe(elements));
-                        }
-                    };
-                    if let Some(mode) = mode {
-                        let request = ApiCall::SwitchMode(mode);
-                        sherlock.await_request(request);
+                    // Piped input starts out as an empty list that is filled as the input arrives
+                    let mode = if stream.is_some() {
+                        SherlockModes::Pipe(String::new())
                     } else {
-                        let mode = SherlockModes::Search;
-                        let request = ApiCall::SwitchMode(mode);
-                        sherlock.await_request(request);
-                    }
+                        SherlockModes::Search
+                    };
+                    sherlock.await_request(ApiCall::SwitchMode(mode));
                     if error_view_active {
                         let mode = SherlockModes::Error;
                         let request = ApiCall::SwitchMode(mode);
@@ -181,6 +171,62 @@ async fn main() {
                 if let Err(error) = Loader::load_css(true) {
                     let _result = error.insert(false);
                 }
+
+                // Append the input as it arrives
+                if let Some(receiver) = stream {
+                    sherlock.borrow_mut().request(ApiCall::PipeOpen(true));
+                    let mut received = false;
+                    while let Ok(mut batch) = receiver.recv().await {
+                        // Raw text and JSON documents can only be displayed once stdin is closed
+                        if !received && !sherlock_flags.dmenu && (sherlock_flags.display_raw || !PipedData::is_streamable(&batch)) {
+                            let pipe = Loader::collect_pipe_args(batch, &receiver).await;
+                            let mut sherlock = sherlock.borrow_mut();
+                            let mut mode: Option<SherlockModes> = None;
+                            if sherlock_flags.display_raw {
+                                let pipe = String::from_utf8_lossy(&pipe).to_string();
+                                mode = Some(SherlockModes::DisplayRaw(pipe));
+                            } else if let Some(mut data) = PipedData::new(&pipe){
+                                if let Some(settings) = data.settings.take(){
+                                    settings.into_iter().for_each(|request| {
+                                        sherlock.request(request);
+                                    });
+                                }
+                                mode = data.elements.take().map(|elements| SherlockModes::Pipe(elements));
+                            }
+                            if let Some(mode) = mode {
+                                sherlock.request(ApiCall::SwitchMode(mode));
+                                if let Some(row) = sherlock_flags.selected_row {
+                                    sherlock.request(ApiCall::SelectIndex(row));
+                                }
+                                if error_view_active {
+                                    sherlock.request(ApiCall::SwitchMode(SherlockModes::Error));
+                                }
+                                received = true;
+                            }
+                            break;
+                        }
+                        // Everything that arrived in the meantime is added at once
+                        while let Ok(chunk) = receiver.try_recv() {
+                            batch.extend(chunk);
+                        }
+                        let pipe = String::from_utf8_lossy(&batch).to_string();
+                        let mut sherlock = sherlock.borrow_mut();
+                        sherlock.request(ApiCall::AppendPipe(pipe));
+                        if let Some(row) = sherlock_flags.selected_row.filter(|_| !received) {
+                            sherlock.request(ApiCall::SelectIndex(row));
+                        }
+                        received = true;
+                    }
+                    let mut sherlock = sherlock.borrow_mut();
+                    // Without any usable input there is nothing to pick from
+                    if !received && !sherlock_flags.dmenu {
+                        sherlock.request(ApiCall::SwitchMode(SherlockModes::Search));
+                        if error_view_active {
+                            sherlock.request(ApiCall::SwitchMode(SherlockModes::Error));
+                        }
+                    }
+                    sherlock.request(ApiCall::PipeOpen(false));
+                }
             }
         });
 
@@ -206,6 +252,16 @@ async fn main() {
     });
     application.run();
     drop(lock);
+
+    // Exit codes requested by custom bindings
+    let code = daemon::daemon::EXIT_CODE.get();
+    if code != 0 {
+        process::exit(code);
+    }
+    // Like dmenu, exit with 1 if the window was closed without selecting anything
+    if dmenu && !daemon::daemon::response_printed() {
+        process::exit(1);
+    }
 }
 
 async fn startup_loading() -> (
@@ -220,8 +276,25 @@ async fn startup_loading() -> (
     let mut non_breaking: Vec<SherlockError> = Vec::new();
     let mut startup_errors: Vec<SherlockError> = Vec::new();
 
+    // Setup flags
+    let sherlock_flags = Loader::load_flags()
+        .map_err(|e| startup_errors.push(e))
+        .unwrap_or_default();
+
+    // Resolve socket and lock locations inside the per-user runtime directory
+    let paths = RuntimePaths::new(sherlock_flags.socket.clone()).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    let paths = RUNTIME_PATHS.get_or_init(|| paths);
+
+    // Subcommands run without starting the ui
+    if let Some(command) = &sherlock_flags.command {
+        process::exit(application::commands::run(command, &sherlock_flags));
+    }
+
     // Check for '.lock'-file to only start a single instance
-    let lock = lock::ensure_single_instance(LOCK_FILE).unwrap_or_else(|_| {
+    let lock = lock::ensure_single_instance(&paths.lock).unwrap_or_else(|_| {
         process::exit(1);
     });
 
@@ -239,11 +312,6 @@ async fn startup_loading() -> (
         application
     };
 
-    // Setup flags
-    let sherlock_flags = Loader::load_flags()
-        .map_err(|e| startup_errors.push(e))
-        .unwrap_or_default();
-
     // Parse configs from 'config.toml'
     let app_config = SherlockConfig::from_flags(&sherlock_flags).map_or_else(
         |e| {
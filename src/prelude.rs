@@ -1,4 +1,4 @@
-use std::{borrow::Cow, cell::RefCell, collections::HashSet, fmt::Debug, rc::Rc, time::SystemTime};
+use std::{borrow::Cow, cell::RefCell, fmt::Debug, rc::Rc, time::SystemTime};
 
 use gdk_pixbuf::subclass::prelude::ObjectSubclassIsExt;
 use gio::{
@@ -16,30 +16,28 @@ use gtk4::{
     SingleSelection, Stack, StackPage,
 };
 
-use crate::{g_subclasses::sherlock_row::SherlockRow, loader::pipe_loader::PipedElements};
+use crate::{
+    g_subclasses::sherlock_row::SherlockRow,
+    loader::pipe_loader::PipedElements,
+    utils::fuzzy::{fuzzy_match, FuzzyMatch},
+};
 
 /// Custom string matching
 pub trait SherlockSearch {
-    fn fuzzy_match<'a, T: Into<Cow<'a, str>> + Debug>(&self, substring: T) -> bool;
+    fn fuzzy_match<'a, T: Into<Cow<'a, str>> + Debug>(&self, substring: T) -> Option<FuzzyMatch>;
 }
 
 impl SherlockSearch for String {
-    fn fuzzy_match<'a, T>(&self, substring: T) -> bool
+    fn fuzzy_match<'a, T>(&self, substring: T) -> Option<FuzzyMatch>
     where
         T: Into<Cow<'a, str>> + Debug,
     {
-        let lowercase = substring.into().to_lowercase();
-        let char_pattern: HashSet<char> = lowercase.chars().collect();
-        let concat_str: String = self
-            .to_lowercase()
-            .chars()
-            .filter(|s| char_pattern.contains(s) || *s == ';')
-            .collect();
-        concat_str.contains(&lowercase)
+        let query: Cow<'a, str> = substring.into();
+        fuzzy_match(&query, self)
     }
 }
 impl SherlockSearch for PipedElements {
-    fn fuzzy_match<'a, T>(&self, substring: T) -> bool
+    fn fuzzy_match<'a, T>(&self, substring: T) -> Option<FuzzyMatch>
     where
         T: Into<Cow<'a, str>> + Debug,
     {
@@ -48,17 +46,10 @@ impl SherlockSearch for PipedElements {
             Some(_) => &self.title,
             None => &self.description,
         };
-        if let Some(search_in) = search_in {
-            let lowercase = substring.into().to_lowercase();
-            let char_pattern: HashSet<char> = lowercase.chars().collect();
-            let concat_str: String = search_in
-                .to_lowercase()
-                .chars()
-                .filter(|s| char_pattern.contains(s) || *s == ';')
-                .collect();
-            return concat_str.contains(&lowercase);
-        }
-        return false;
+        let query: Cow<'a, str> = substring.into();
+        search_in
+            .as_ref()
+            .and_then(|search_in| fuzzy_match(&query, search_in))
     }
 }
 /// Apply icon by name or by path if applicable
@@ -231,7 +222,7 @@ impl SherlockNav for ListView {
                 if let Some(row) = selection.item(item).and_downcast::<SherlockRow>() {
                     if row.imp().shortcut.get() {
                         let exit: u8 = 0;
-                        row.emit_by_name::<()>("row-should-activate", &[&exit]);
+                        row.emit_by_name::<()>("row-should-activate", &[&exit, &"shortcut"]);
                         break;
                     }
                 }
@@ -2,10 +2,14 @@ use gio::glib::{
     object::{CastNone, ObjectExt},
     WeakRef,
 };
-use gtk4::{prelude::EditableExt, Entry, ListView};
+use gtk4::{
+    prelude::{EditableExt, WidgetExt},
+    Entry, ListView,
+};
 use std::{cell::RefCell, rc::Rc};
 
 use crate::{
+    daemon::daemon::print_reponse,
     g_subclasses::{action_entry::ContextAction, sherlock_row::SherlockRow},
     prelude::SherlockNav,
 };
@@ -25,7 +29,7 @@ impl KeyActions {
             context,
         }
     }
-    pub fn on_multi_return(&self) {
+    pub fn on_multi_return(&self, trigger: &str) {
         // no context menu yet
         if self.context.open.get() {
             return;
@@ -38,11 +42,12 @@ impl KeyActions {
             let len = actives.len();
             actives.into_iter().enumerate().for_each(|(i, row)| {
                 let exit: u8 = if i < len - 1 { 1 } else { 0 };
-                row.emit_by_name::<()>("row-should-activate", &[&exit]);
+                row.emit_by_name::<()>("row-should-activate", &[&exit, &trigger]);
             });
         }
     }
-    pub fn on_return(&self, context_open: bool, close: Option<bool>) {
+    /// Activates the selected row. `trigger` names the key binding that caused it.
+    pub fn on_return(&self, context_open: bool, close: Option<bool>, trigger: &str) {
         let exit: u8 = close.map_or(0, |v| if v { 2 } else { 1 });
         if context_open {
             // Activate action
@@ -59,11 +64,11 @@ impl KeyActions {
                 .and_then(|r| r.selected_item())
                 .and_downcast::<SherlockRow>()
             {
-                row.emit_by_name::<()>("row-should-activate", &[&exit]);
-            } else {
-                if let Some(current_text) = self.search_bar.upgrade().map(|s| s.text()) {
-                    println!("{}", current_text);
-                }
+                row.emit_by_name::<()>("row-should-activate", &[&exit, &trigger]);
+            } else if let Some(search_bar) = self.search_bar.upgrade() {
+                // Without a matching row the query itself is the answer, as in dmenu
+                let _result = print_reponse(search_bar.text());
+                let _ = search_bar.activate_action("win.close", None);
             }
         }
     }
@@ -4,6 +4,7 @@ pub mod context;
 pub mod error_view;
 pub mod input_window;
 pub mod key_actions;
+pub mod preview;
 pub mod search;
 pub mod util;
 pub mod window;
@@ -0,0 +1,253 @@
+use std::cell::RefCell;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::rc::Rc;
+
+use gdk_pixbuf::Pixbuf;
+use gio::glib::{self, WeakRef};
+use gtk4::{pango::WrapMode, prelude::*, Box as GtkBox, Label, Picture, ScrolledWindow};
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tokio::time::{timeout, Duration};
+
+use crate::g_subclasses::sherlock_row::SherlockRow;
+
+/// Largest part of a file that is loaded for its preview
+const MAX_FILE_SIZE: u64 = 16 * 1024 * 1024;
+/// Number of bytes shown of text files and command output
+const MAX_TEXT_LEN: usize = 64 * 1024;
+
+/// Content of the preview pane for a row
+#[derive(Clone, Debug, PartialEq)]
+pub enum Preview {
+    /// Labeled properties, e.g. the desktop file and exec of an application
+    Details(Vec<(String, String)>),
+    /// A text or image file
+    File(PathBuf),
+    /// Text that is shown as it is
+    Text(String),
+    /// A shell command whose output is shown
+    Command(String),
+}
+impl Preview {
+    /// Creates the preview for `--preview-cmd`. Every `{}` in the template is replaced by the
+    /// quoted selection.
+    pub fn command(template: &str, selection: &str) -> Self {
+        let quoted = format!("'{}'", selection.replace('\'', r"'\''"));
+        Self::Command(template.replace("{}", &quoted))
+    }
+}
+
+/// A loaded preview that is ready to be displayed
+enum PreviewContent {
+    Details(Vec<(String, String)>),
+    Text(String),
+    Image(Pixbuf),
+}
+
+/// Shows the preview of `row` in `preview_box`, or hides the pane if the row has none. The
+/// content is loaded in the background. Loading is cancelled once another row gets selected.
+pub fn update_preview(
+    preview_box: &WeakRef<GtkBox>,
+    row: Option<SherlockRow>,
+    current_task: &Rc<RefCell<Option<glib::JoinHandle<()>>>>,
+) -> Option<()> {
+    if let Some(task) = current_task.borrow_mut().take() {
+        task.abort();
+    };
+    let preview_box = preview_box.upgrade()?;
+    let Some(preview) = row.and_then(|row| row.preview()) else {
+        clear(&preview_box);
+        set_open(&preview_box, false);
+        return None;
+    };
+    let task = glib::MainContext::default().spawn_local({
+        let preview_box = preview_box.downgrade();
+        let current_task = Rc::clone(current_task);
+        async move {
+            let content = load(preview).await;
+            if let Some(preview_box) = preview_box.upgrade() {
+                match content {
+                    Some(content) => show(&preview_box, content),
+                    None => {
+                        clear(&preview_box);
+                        set_open(&preview_box, false);
+                    }
+                }
+            }
+            *current_task.borrow_mut() = None;
+        }
+    });
+    *current_task.borrow_mut() = Some(task);
+    Some(())
+}
+
+/// Loads the content of `preview`. Returns `None` if a file preview does not point to a file.
+async fn load(preview: Preview) -> Option<PreviewContent> {
+    let result = match preview {
+        Preview::Details(details) => return Some(PreviewContent::Details(details)),
+        Preview::Text(text) => return Some(PreviewContent::Text(text)),
+        Preview::File(path) => {
+            let metadata = tokio::fs::metadata(&path).await;
+            if !metadata.is_ok_and(|metadata| metadata.is_file()) {
+                return None;
+            }
+            read_file(&path).await
+        }
+        Preview::Command(cmd) => run_command(&cmd).await,
+    };
+    match result {
+        Ok(bytes) => Some(from_bytes(bytes)),
+        Err(message) => Some(PreviewContent::Text(message)),
+    }
+}
+
+async fn read_file(path: &Path) -> Result<Vec<u8>, String> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut bytes = Vec::new();
+    file.take(MAX_FILE_SIZE)
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(bytes)
+}
+
+async fn run_command(cmd: &str) -> Result<Vec<u8>, String> {
+    // The child is killed if the preview is cancelled
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to run preview command: {}", e))?;
+    let output = timeout(Duration::from_secs(5), child.wait_with_output())
+        .await
+        .map_err(|_| String::from("Preview command timed out."))?
+        .map_err(|e| format!("Failed to run preview command: {}", e))?;
+    if output.stdout.is_empty() {
+        Ok(output.stderr)
+    } else {
+        Ok(output.stdout)
+    }
+}
+
+/// Shows text as text and tries to decode everything else as an image
+fn from_bytes(bytes: Vec<u8>) -> PreviewContent {
+    if let Some(text) = text_excerpt(&bytes) {
+        return PreviewContent::Text(text);
+    }
+    match Pixbuf::from_read(Cursor::new(bytes)) {
+        Ok(pixbuf) => PreviewContent::Image(pixbuf),
+        Err(_) => PreviewContent::Text(String::from("Binary data")),
+    }
+}
+
+/// Returns the beginning of `bytes` as text, or `None` if it looks like binary data
+fn text_excerpt(bytes: &[u8]) -> Option<String> {
+    let head = &bytes[..bytes.len().min(MAX_TEXT_LEN)];
+    if head.contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(head).into_owned())
+}
+
+fn show(preview_box: &GtkBox, content: PreviewContent) {
+    clear(preview_box);
+    let child: gtk4::Widget = match content {
+        PreviewContent::Details(details) => {
+            let list = GtkBox::new(gtk4::Orientation::Vertical, 0);
+            for (key, value) in details {
+                let key = Label::builder()
+                    .label(&key)
+                    .xalign(0.0)
+                    .css_classes(["preview-key"])
+                    .build();
+                let value = Label::builder()
+                    .label(&value)
+                    .xalign(0.0)
+                    .wrap(true)
+                    .wrap_mode(WrapMode::WordChar)
+                    .css_classes(["preview-value"])
+                    .build();
+                list.append(&key);
+                list.append(&value);
+            }
+            list.upcast()
+        }
+        PreviewContent::Text(text) => Label::builder()
+            .label(&text)
+            .xalign(0.0)
+            .yalign(0.0)
+            .wrap(true)
+            .wrap_mode(WrapMode::WordChar)
+            .css_classes(["preview-text"])
+            .build()
+            .upcast(),
+        PreviewContent::Image(pixbuf) => {
+            let texture = gtk4::gdk::Texture::for_pixbuf(&pixbuf);
+            Picture::for_paintable(&texture).upcast()
+        }
+    };
+    let viewport = ScrolledWindow::builder()
+        .child(&child)
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .vexpand(true)
+        .build();
+    preview_box.append(&viewport);
+    set_open(preview_box, true);
+}
+
+fn clear(preview_box: &GtkBox) {
+    while let Some(child) = preview_box.first_child() {
+        preview_box.remove(&child);
+    }
+}
+
+/// Shows or hides the pane. The split view gets the `preview-open` class to make room for it.
+fn set_open(preview_box: &GtkBox, open: bool) {
+    preview_box.set_visible(open);
+    if let Some(split_view) = preview_box.parent() {
+        if open {
+            split_view.add_css_class("preview-open");
+        } else {
+            split_view.remove_css_class("preview-open");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_preview_commands() {
+        assert_eq!(
+            Preview::command("head -n 20 {}", "notes.md"),
+            Preview::Command(String::from("head -n 20 'notes.md'"))
+        );
+        assert_eq!(
+            Preview::command("echo {}", "it's $HOME"),
+            Preview::Command(String::from(r"echo 'it'\''s $HOME'"))
+        );
+        assert_eq!(
+            Preview::command("date", "ignored"),
+            Preview::Command(String::from("date"))
+        );
+    }
+
+    #[test]
+    fn detects_binary_data() {
+        assert_eq!(text_excerpt(b"hello\n").as_deref(), Some("hello\n"));
+        assert!(text_excerpt(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").is_none());
+        assert_eq!(
+            text_excerpt(&vec![b'a'; MAX_TEXT_LEN + 10]).map(|text| text.len()),
+            Some(MAX_TEXT_LEN)
+        );
+    }
+}
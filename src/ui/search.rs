@@ -8,19 +8,24 @@ use gtk4::{
     SignalListItemFactory, SingleSelection, SortListModel,
 };
 use gtk4::{glib, ApplicationWindow, Entry};
-use levenshtein::levenshtein;
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 
 use super::context::make_context;
+use super::preview::update_preview;
 use super::util::*;
 use crate::{
-    api::{api::SherlockAPI, call::ApiCall, server::SherlockServer},
+    api::{api::SherlockAPI, call::ApiCall, events::SherlockEvent, server::SherlockServer},
+    daemon::daemon::EXIT_CODE,
     g_subclasses::sherlock_row::SherlockRow,
-    prelude::{IconComp, SherlockNav, SherlockSearch, ShortCut},
+    loader::util::SelectionReader,
+    prelude::{IconComp, SherlockNav, ShortCut},
     ui::key_actions::KeyActions,
-    utils::config::{default_search_icon, default_search_icon_back},
+    utils::{
+        config::{default_search_icon, default_search_icon_back},
+        fuzzy::fuzzy_match_with,
+    },
 };
 use crate::{
     sherlock_error,
@@ -65,14 +70,23 @@ pub fn search(
             let results = imp.results.downgrade();
             let context_model = context.model.clone();
             let current_mode = Rc::clone(&mode);
-            move |_myself, _position, _removed, added| {
+            move |_myself, position, _removed, added| {
                 if added == 0 {
                     return;
                 }
+                let Some(results) = results.upgrade() else {
+                    return;
+                };
+                // Rows appended to a streamed pipe keep the current selection
+                let has_selection = results
+                    .model()
+                    .and_downcast::<SingleSelection>()
+                    .is_some_and(|s| s.selected() != u32::MAX);
+                if position > 0 && has_selection {
+                    return;
+                }
                 // Show or hide context menu shortcuts whenever stack shows
-                results
-                    .upgrade()
-                    .map(|r| r.focus_first(Some(&context_model), Some(current_mode.clone())));
+                results.focus_first(Some(&context_model), Some(current_mode.clone()));
             }
         });
     }
@@ -103,6 +117,8 @@ pub fn search(
             let mode_clone = Rc::clone(&mode);
             let modes_clone = Rc::clone(&handler.modes);
             let ui = ui.downgrade();
+            // A dmenu prompt replaces the default mode titles
+            let prompt = CONFIG.get().and_then(|c| c.runtime.prompt.clone());
             move |_, action, parameter| {
                 let state = action.state().and_then(|s| s.get::<String>());
                 let parameter = parameter.and_then(|p| p.get::<String>());
@@ -116,9 +132,11 @@ pub fn search(
                     match parameter.as_str() {
                         "search" => {
                             imp.search_icon_holder.set_css_classes(&["back"]);
-                            imp.mode_title.set_text("Search");
+                            imp.mode_title
+                                .set_text(prompt.as_deref().unwrap_or("Search"));
                         }
                         _ => {
+                            let previous = state.clone();
                             parameter.push_str(" ");
                             let mode_name = modes_clone.borrow().get(&parameter).cloned();
                             match mode_name {
@@ -131,7 +149,7 @@ pub fn search(
                                 }
                                 _ => {
                                     imp.search_icon_holder.set_css_classes(&["search"]);
-                                    imp.mode_title.set_text("All");
+                                    imp.mode_title.set_text(prompt.as_deref().unwrap_or("All"));
 
                                     parameter = String::from("all ");
                                     *mode_clone.borrow_mut() = parameter.clone();
@@ -139,6 +157,12 @@ pub fn search(
                                 }
                             }
                             action.set_state(&state.to_variant());
+                            if state != previous {
+                                SherlockEvent::ModeSwitched {
+                                    mode: state.trim().to_string(),
+                                }
+                                .emit();
+                            }
                         }
                     }
                 }
@@ -356,6 +380,17 @@ fn construct_window(
     let selection = SingleSelection::new(Some(sorted_model));
     imp.results.set_model(Some(&selection));
 
+    // Show details about the selected row in the preview pane
+    imp.preview_box.set_visible(false);
+    let preview_task: Rc<RefCell<Option<glib::JoinHandle<()>>>> = Rc::new(RefCell::new(None));
+    selection.connect_selected_item_notify({
+        let preview_box = imp.preview_box.downgrade();
+        move |selection| {
+            let row = selection.selected_item().and_downcast::<SherlockRow>();
+            update_preview(&preview_box, row, &preview_task);
+        }
+    });
+
     imp.results.set_model(Some(&selection));
     imp.results.set_factory(Some(&factory));
 
@@ -375,6 +410,7 @@ fn construct_window(
         sorter.downgrade(),
         custom_binds,
         first_iter,
+        Rc::clone(&mode),
     );
 
     if config.expand.enable {
@@ -382,6 +418,15 @@ fn construct_window(
             .set_max_content_height(config.appearance.height);
         imp.result_viewport.set_propagate_natural_height(true);
     }
+    if let Some(lines) = config.runtime.lines {
+        // A tile is 50px high plus 5px margin and a 1px border on each side
+        imp.result_viewport
+            .set_max_content_height(lines as i32 * 57);
+        imp.result_viewport.set_propagate_natural_height(true);
+    }
+    if let Some(prompt) = &config.runtime.prompt {
+        imp.mode_title.set_text(prompt);
+    }
 
     // disable status bar
     if !config.appearance.status_bar {
@@ -412,6 +457,7 @@ fn make_filter(search_text: &Rc<RefCell<String>>, mode: &Rc<RefCell<String>>) ->
     CustomFilter::new({
         let search_text = Rc::clone(search_text);
         let search_mode = Rc::clone(mode);
+        let case_sensitive = CONFIG.get().map_or(false, |c| c.runtime.case_sensitive);
         move |entry| {
             let item = entry.downcast_ref::<SherlockRow>().unwrap();
             let (home, only_home) = item.home();
@@ -423,11 +469,23 @@ fn make_filter(search_text: &Rc<RefCell<String>>, mode: &Rc<RefCell<String>>) ->
             let update_res = item.update(&current_text);
 
             if is_home {
+                item.set_search_match(None);
+                item.set_learned(0);
                 if home || only_home {
                     return true;
                 }
                 return false;
             } else {
+                // Store the match so the sorter can rank on the same score
+                let search_match = fuzzy_match_with(&current_text, &item.search(), case_sensitive);
+                let is_match = search_match.is_some();
+                item.set_search_match(search_match);
+                let learned = match item.counter_key().as_str() {
+                    "" => 0,
+                    key => SelectionReader::lookup(&current_text, key),
+                };
+                item.set_learned(learned);
+
                 let alias = item.alias();
                 let priority = item.priority();
                 if mode != "all" {
@@ -447,7 +505,7 @@ fn make_filter(search_text: &Rc<RefCell<String>>, mode: &Rc<RefCell<String>>) ->
                 if update_res {
                     return true;
                 }
-                item.search().fuzzy_match(&current_text)
+                is_match
             }
         }
     })
@@ -455,41 +513,32 @@ fn make_filter(search_text: &Rc<RefCell<String>>, mode: &Rc<RefCell<String>>) ->
 fn make_sorter(search_text: &Rc<RefCell<String>>) -> CustomSorter {
     CustomSorter::new({
         let search_text = Rc::clone(search_text);
-        fn search_score(query: &str, match_in: &str) -> f32 {
-            if match_in.len() == 0 {
-                return 0.0;
-            }
-            let distance = levenshtein(query, match_in) as f32;
-            let normed = (distance / match_in.len() as f32).clamp(0.2, 1.0);
-            let starts_with = if match_in.starts_with(query) {
-                -0.2
-            } else {
-                0.0
-            };
-            normed + starts_with
-        }
-
-        fn make_prio(prio: f32, query: &str, match_in: &str) -> f32 {
-            let score = search_score(query, match_in);
-            // shift counts 3 to right; 1.34 → 1.00034 to make room for levenshtein
-            let counters = prio.fract() / 1000.0;
-            prio.trunc() + (counters + score).min(0.99)
-        }
         move |item_a, item_b| {
             let search_text = search_text.borrow();
 
             let item_a = item_a.downcast_ref::<SherlockRow>().unwrap();
             let item_b = item_b.downcast_ref::<SherlockRow>().unwrap();
 
-            let mut priority_a = item_a.priority();
-            let mut priority_b = item_b.priority();
+            let priority_a = item_a.priority();
+            let priority_b = item_b.priority();
 
-            if !search_text.is_empty() {
-                priority_a = make_prio(item_a.priority(), &search_text, &item_a.search());
-                priority_b = make_prio(item_b.priority(), &search_text, &item_b.search());
+            if search_text.is_empty() {
+                return priority_a.total_cmp(&priority_b).into();
             }
 
-            priority_a.total_cmp(&priority_b).into()
+            // Launcher priority first, then rows previously activated for this query, the
+            // fuzzy score (higher is better) and the execution counts as a tie breaker. Rows
+            // that are shown without matching the query (e.g. keyword aware tiles) keep their
+            // place at the top of their launcher.
+            let score_a = item_a.search_score().unwrap_or(i32::MAX);
+            let score_b = item_b.search_score().unwrap_or(i32::MAX);
+            priority_a
+                .trunc()
+                .total_cmp(&priority_b.trunc())
+                .then_with(|| item_b.learned().cmp(&item_a.learned()))
+                .then_with(|| score_b.cmp(&score_a))
+                .then_with(|| priority_a.total_cmp(&priority_b))
+                .into()
         }
     })
 }
@@ -522,11 +571,27 @@ fn nav_event(
                 key_matches && mod_matches
             };
 
+            // Custom bindings select the row and report their action as the trigger
+            if let Some(bind) = binds.find_custom(key, mods) {
+                // The exit code only applies if an activated row closes the window
+                EXIT_CODE.stage(bind.exit.unwrap_or(0));
+                let trigger = bind.action.as_deref().unwrap_or(&bind.key);
+                if multi {
+                    key_actions.on_multi_return(trigger);
+                } else {
+                    key_actions.on_return(key_actions.context.open.get(), None, trigger);
+                }
+                EXIT_CODE.stage(0);
+                return true.into();
+            }
+
             match key {
                 // Inplace execution of commands
-                _ if matches(binds.exec_inplace, binds.exec_inplace_mod) => {
-                    key_actions.on_return(key_actions.context.open.get(), Some(false))
-                }
+                _ if matches(binds.exec_inplace, binds.exec_inplace_mod) => key_actions.on_return(
+                    key_actions.context.open.get(),
+                    Some(false),
+                    "exec_inplace",
+                ),
 
                 // Context menu opening
                 _ if matches(binds.context, binds.context_mod) => {
@@ -578,10 +643,10 @@ fn nav_event(
                     return false.into();
                 }
                 Key::Return if multi => {
-                    key_actions.on_multi_return();
+                    key_actions.on_multi_return("return");
                 }
                 Key::Return | Key::KP_Enter => {
-                    key_actions.on_return(key_actions.context.open.get(), None);
+                    key_actions.on_return(key_actions.context.open.get(), None, "return");
                 }
                 Key::Escape if key_actions.context.open.get() => {
                     key_actions.close_context();
@@ -663,6 +728,13 @@ fn change_event(
                 current_text.clear();
             }
             *search_query_clone.borrow_mut() = current_text.clone();
+            // Obfuscated input is not shared with subscribers
+            if EntryExt::is_visible(search_bar) {
+                SherlockEvent::QueryChanged {
+                    query: current_text,
+                }
+                .emit();
+            }
             // filter and sort
             if let Some(res) = results.upgrade() {
                 // To reload ui according to mode
@@ -2,6 +2,7 @@ use gdk_pixbuf::subclass::prelude::ObjectSubclassIsExt;
 use gtk4::prelude::*;
 use std::cell::RefCell;
 use std::collections::HashSet;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 use crate::actions::{execute_from_attrs, get_attrs_map};
@@ -9,6 +10,7 @@ use crate::g_subclasses::sherlock_row::SherlockRow;
 use crate::launcher::Launcher;
 use crate::loader::util::AppData;
 use crate::prelude::IconComp;
+use crate::ui::preview::Preview;
 use crate::CONFIG;
 
 use super::util::update_tag;
@@ -16,6 +18,12 @@ use super::Tile;
 
 impl Tile {
     pub fn app_tile(launcher: &Launcher, commands: &HashSet<AppData>) -> Vec<SherlockRow> {
+        let subtitles = CONFIG
+            .get()
+            .map_or(false, |config| config.appearance.subtitles);
+        let preview = CONFIG
+            .get()
+            .map_or(false, |config| config.appearance.preview);
         commands
             .into_iter()
             .map(|value| {
@@ -26,6 +34,12 @@ impl Tile {
                 object.append(&tile);
                 object.set_css_classes(&vec!["tile"]);
 
+                // Show the desktop file's comment below the name
+                if let Some(comment) = value.comment.as_deref().filter(|_| subtitles) {
+                    imp.subtitle.set_text(comment);
+                    imp.subtitle.set_visible(true);
+                }
+
                 // Icon stuff
                 imp.icon.set_icon(
                     value.icon.as_deref(),
@@ -44,11 +58,22 @@ impl Tile {
                     let row_weak = object.downgrade();
 
                     let launcher = launcher.clone();
+                    let desktop_file = value
+                        .desktop_file
+                        .as_ref()
+                        .map(|path| path.to_string_lossy().to_string());
+                    let dbus_app_id = value.dbus_activatable.then(|| value.app_id()).flatten();
                     let attrs = get_attrs_map(vec![
                         ("method", Some(&launcher.method)),
                         ("exec", value.exec.as_deref()),
                         ("term", Some(&value.terminal.to_string())),
                         ("exit", Some(&launcher.exit.to_string())),
+                        ("name", Some(&value.name)),
+                        ("icon", value.icon.as_deref()),
+                        ("desktop_file", desktop_file.as_deref()),
+                        ("dbus_app_id", dbus_app_id.as_deref()),
+                        ("working_dir", value.working_dir.as_deref()),
+                        ("wm_class", value.wm_class.as_deref()),
                     ]);
                     let attrs_rc = Rc::new(RefCell::new(attrs));
                     let name = value.name.clone();
@@ -102,8 +127,12 @@ impl Tile {
                 };
 
                 object.set_update(update_closure);
+                object.set_highlight_label(imp.title.downgrade());
                 object.with_launcher(launcher);
                 object.with_appdata(&value);
+                if let Some(preview) = preview.then(|| app_preview(value)).flatten() {
+                    object.set_preview(preview);
+                }
                 object.add_actions(&launcher.add_actions);
                 if launcher.shortcut {
                     object.set_shortcut_holder(Some(imp.shortcut_holder.downgrade()));
@@ -114,6 +143,37 @@ impl Tile {
     }
 }
 
+/// Describes an application for the preview pane. Entries without a desktop file that point to
+/// a file, like themes, preview the file instead. Whether the file exists is checked once the
+/// preview is loaded.
+fn app_preview(value: &AppData) -> Option<Preview> {
+    let Some(desktop_file) = &value.desktop_file else {
+        let path = PathBuf::from(value.exec.as_deref()?);
+        return path.is_absolute().then(|| Preview::File(path));
+    };
+    let actions: Vec<&str> = value
+        .actions
+        .iter()
+        .filter_map(|action| action.name.as_deref())
+        .collect();
+    let details = [
+        (
+            "Desktop File",
+            Some(desktop_file.to_string_lossy().to_string()),
+        ),
+        ("Exec", value.exec.clone()),
+        (
+            "Categories",
+            (!value.categories.is_empty()).then(|| value.categories.join(", ")),
+        ),
+        ("Actions", (!actions.is_empty()).then(|| actions.join(", "))),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| Some((key.to_string(), value?)))
+    .collect();
+    Some(Preview::Details(details))
+}
+
 mod imp {
     use gtk4::glib;
     use gtk4::subclass::prelude::*;
@@ -126,6 +186,9 @@ mod imp {
         #[template_child(id = "app-name")]
         pub title: TemplateChild<Label>,
 
+        #[template_child(id = "app-subtitle")]
+        pub subtitle: TemplateChild<Label>,
+
         #[template_child(id = "launcher-type")]
         pub category: TemplateChild<Label>,
 
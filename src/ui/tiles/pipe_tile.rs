@@ -5,6 +5,8 @@ use crate::actions::get_attrs_map;
 use crate::g_subclasses::sherlock_row::SherlockRow;
 use crate::loader::pipe_loader::PipedElements;
 use crate::prelude::IconComp;
+use crate::ui::preview::Preview;
+use crate::CONFIG;
 use gdk_pixbuf::subclass::prelude::ObjectSubclassIsExt;
 use gdk_pixbuf::Pixbuf;
 use gio::glib::object::ObjectExt;
@@ -16,10 +18,24 @@ use super::app_tile::AppTile;
 use super::Tile;
 
 impl Tile {
-    pub fn pipe_data(lines: &Vec<PipedElements>, method: &str) -> Vec<SherlockRow> {
+    /// Creates rows for piped elements. `first_index` is the index of the first element within
+    /// the whole piped input.
+    pub fn pipe_data(
+        lines: &Vec<PipedElements>,
+        method: &str,
+        first_index: usize,
+    ) -> Vec<SherlockRow> {
         let mut results: Vec<SherlockRow> = Vec::with_capacity(lines.len());
+        // Dmenu's `-format i|d` prints the index of the row as well
+        let output_format = CONFIG.get().and_then(|c| {
+            c.runtime
+                .output_format
+                .clone()
+                .or_else(|| c.runtime.format.clone())
+        });
+        let preview_cmd = CONFIG.get().and_then(|c| c.runtime.preview_cmd.clone());
 
-        for item in lines {
+        for (i, item) in lines.iter().enumerate() {
             let search = format!(
                 "{};{}",
                 item.title.as_deref().unwrap_or(""),
@@ -82,6 +98,39 @@ impl Tile {
                 object.set_home(true);
                 object.set_priority(1.0);
                 object.set_search(&search);
+                object.set_highlight_label(imp.title.downgrade());
+                let index = first_index + i;
+
+                // Context menu actions
+                if let Some(actions) = &item.actions {
+                    let actions = actions
+                        .iter()
+                        .filter(|action| action.is_valid())
+                        .cloned()
+                        .map(|mut action| {
+                            // Printing actions report the element with the action as its trigger
+                            if let Some(format) = &output_format {
+                                if action.method == "print" {
+                                    let trigger = action.name.as_deref().unwrap_or_default();
+                                    action.exec =
+                                        item.output(format, index, trigger).or(action.exec);
+                                }
+                            }
+                            action
+                        })
+                        .collect();
+                    object.set_actions(actions);
+                }
+
+                // Preview pane
+                if let Some(preview) = &item.preview {
+                    object.set_preview(Preview::Text(preview.clone()));
+                } else if let (Some(cmd), Some(selection)) = (&preview_cmd, result) {
+                    object.set_preview(Preview::command(cmd, selection));
+                }
+
+                let element = item.clone();
+                let output_format = output_format.clone();
                 object.connect_local("row-should-activate", false, move |args| {
                     let row = args.first().map(|f| f.get::<SherlockRow>().ok())??;
                     let param: u8 = args.get(1).and_then(|v| v.get::<u8>().ok())?;
@@ -90,7 +139,20 @@ impl Tile {
                         2 => Some(true),
                         _ => None,
                     };
-                    execute_from_attrs(&row, &attrs, param);
+                    let trigger: String = args.get(2).and_then(|v| v.get().ok())?;
+                    // Hand the formatted element to the action as the field to output
+                    let output = output_format
+                        .as_deref()
+                        .and_then(|format| element.output(format, index, &trigger));
+                    match output {
+                        Some(output) => {
+                            let mut attrs = attrs.clone();
+                            attrs.insert(String::from("output"), output);
+                            attrs.insert(String::from("field"), String::from("output"));
+                            execute_from_attrs(&row, &attrs, param);
+                        }
+                        None => execute_from_attrs(&row, &attrs, param),
+                    }
                     None
                 });
                 results.push(object);
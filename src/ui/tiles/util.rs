@@ -1,5 +1,5 @@
 use crate::{g_subclasses::sherlock_row::SherlockRow, CONFIG};
-use gio::glib::WeakRef;
+use gio::glib::{self, WeakRef};
 use gtk4::{prelude::*, Box, Builder, Image, Label, Overlay, Spinner, TextView};
 
 #[derive(Default)]
@@ -128,3 +128,30 @@ pub fn update_tag(label: &WeakRef<Label>, content: &Option<String>, keyword: &st
     }
     None
 }
+
+/// Wraps the characters at the given (char) indices into bold Pango markup
+/// * **text**: The raw text displayed in the label
+/// * **indices**: Sorted character positions that should be highlighted
+pub fn highlight_markup(text: &str, indices: &[usize]) -> String {
+    let mut markup = String::with_capacity(text.len() + indices.len() * 7);
+    let mut indices = indices.iter().peekable();
+    let mut open = false;
+    let mut buf = [0u8; 4];
+    for (i, c) in text.chars().enumerate() {
+        let matched = indices.peek() == Some(&&i);
+        if matched {
+            indices.next();
+        }
+        if matched && !open {
+            markup.push_str("<b>");
+        } else if !matched && open {
+            markup.push_str("</b>");
+        }
+        open = matched;
+        markup.push_str(&glib::markup_escape_text(c.encode_utf8(&mut buf)));
+    }
+    if open {
+        markup.push_str("</b>");
+    }
+    markup
+}
@@ -32,6 +32,9 @@ impl Tile {
         // Construct attrs and enable action capabilities
         object.with_launcher(&launcher);
         object.set_keyword_aware(true);
+        object.set_search(&web.display_name);
+        object.set_counter_key(&format!("websearch-{}", web.engine));
+        object.set_highlight_label(imp.title.downgrade());
 
         let update_closure = {
             let tag_start = imp.tag_start.downgrade();
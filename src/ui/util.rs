@@ -18,7 +18,7 @@ use serde::Deserialize;
 
 use crate::g_subclasses::sherlock_row::SherlockRow;
 use crate::loader::Loader;
-use crate::utils::config::default_modkey_ascii;
+use crate::utils::config::{default_modkey_ascii, PipeBind};
 use crate::utils::errors::{SherlockError, SherlockErrorType};
 use crate::{sherlock_error, CONFIG};
 
@@ -43,6 +43,14 @@ pub struct ConfKeys {
     // Shortcuts
     pub shortcut_modifier: Option<ModifierType>,
     pub shortcut_modifier_str: String,
+    // Custom bindings of pipe mode, can be extended at runtime
+    pub custom: Rc<RefCell<Vec<CustomBind>>>,
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomBind {
+    pub key: Key,
+    pub modifier: Option<ModifierType>,
+    pub bind: PipeBind,
 }
 impl ConfKeys {
     pub fn new() -> Self {
@@ -69,7 +77,7 @@ impl ConfKeys {
             };
             let shortcut_modifier_str = ConfKeys::get_mod_str(&shortcut_modifier);
             let context_mod_str = ConfKeys::get_mod_str(&context_mod);
-            return ConfKeys {
+            let keys = ConfKeys {
                 next: next.0,
                 next_mod,
                 prev: prev.0,
@@ -82,10 +90,55 @@ impl ConfKeys {
                 context_mod_str,
                 shortcut_modifier,
                 shortcut_modifier_str,
+                custom: Rc::new(RefCell::new(Vec::new())),
             };
+            c.runtime
+                .binds
+                .iter()
+                .for_each(|bind| keys.add_custom(bind));
+            return keys;
         }
         ConfKeys::empty()
     }
+    /// Registers a custom binding. Unknown keys or modifiers are reported as a warning.
+    pub fn add_custom(&self, bind: &PipeBind) {
+        let (modifier, (key, _)) = ConfKeys::eval_bind_combination(&bind.key);
+        // An unknown modifier must not turn the binding into a plain key
+        let has_modifier = bind.key.contains('-');
+        match key {
+            Some(key) if modifier.is_some() || !has_modifier => {
+                self.custom.borrow_mut().push(CustomBind {
+                    key,
+                    modifier,
+                    bind: bind.clone(),
+                })
+            }
+            _ => {
+                let _result = sherlock_error!(
+                    SherlockErrorType::ConfigError(Some(format!(
+                        "Unknown key combination \"{}\" in binding",
+                        bind.key
+                    ))),
+                    ""
+                )
+                .insert(false);
+            }
+        }
+    }
+    /// Returns the custom binding for a key press. Bindings without a modifier only match if
+    /// no modifier is held.
+    pub fn find_custom(&self, key: Key, mods: ModifierType) -> Option<PipeBind> {
+        self.custom
+            .borrow()
+            .iter()
+            .find(|custom| {
+                custom.key == key
+                    && custom
+                        .modifier
+                        .map_or(mods.is_empty(), |modifier| mods.contains(modifier))
+            })
+            .map(|custom| custom.bind.clone())
+    }
     pub fn empty() -> Self {
         ConfKeys {
             next: None,
@@ -100,6 +153,7 @@ impl ConfKeys {
             context_str: None,
             shortcut_modifier: None,
             shortcut_modifier_str: String::new(),
+            custom: Rc::new(RefCell::new(Vec::new())),
         }
     }
     fn eval_bind_combination(key: &str) -> (Option<ModifierType>, (Option<Key>, Option<String>)) {
@@ -121,8 +175,8 @@ impl ConfKeys {
             "end" => (Some(Key::End), Some(String::from("End"))),
             "home" => (Some(Key::Home), Some(String::from("Home"))),
             "return" => (Some(Key::Return), Some(String::from("↩"))),
-            // Alphabet
-            k if k.len() == 1 && k.chars().all(|c| c.is_ascii_alphabetic()) => {
+            // Alphabet and digits
+            k if k.len() == 1 && k.chars().all(|c| c.is_ascii_alphanumeric()) => {
                 (Key::from_name(k), Some(k.to_uppercase()))
             }
             _ => (None, None),
@@ -247,6 +301,7 @@ impl SherlockCounter {
 pub struct SearchHandler {
     pub model: Option<WeakRef<ListStore>>,
     pub modes: Rc<RefCell<HashMap<String, Option<String>>>>,
+    pub mode: Rc<RefCell<String>>,
     pub task: Rc<RefCell<Option<glib::JoinHandle<()>>>>,
     pub error_model: WeakRef<ListStore>,
     pub filter: WeakRef<CustomFilter>,
@@ -262,10 +317,12 @@ impl SearchHandler {
         sorter: WeakRef<CustomSorter>,
         binds: ConfKeys,
         first_iter: Cell<bool>,
+        mode: Rc<RefCell<String>>,
     ) -> Self {
         Self {
             model: Some(model),
             modes: Rc::new(RefCell::new(HashMap::new())),
+            mode,
             task: Rc::new(RefCell::new(None)),
             error_model,
             filter,
@@ -336,7 +393,7 @@ pub struct SearchUI {
     pub all: WeakRef<GtkBox>,
     pub result_viewport: WeakRef<ScrolledWindow>,
     pub results: WeakRef<ListView>,
-    // will be later used for split view to display information about apps/commands
+    // shows information about the selected row
     pub preview_box: WeakRef<GtkBox>,
     pub status_bar: WeakRef<GtkBox>,
     pub search_bar: WeakRef<Entry>,
@@ -1,15 +1,17 @@
 use gio::glib::WeakRef;
-use gio::ActionEntry;
+use gio::{ActionEntry, AppInfo, AppInfoCreateFlags};
 use gtk4::gdk::{Display, Key, Monitor};
 use gtk4::{
     prelude::*, Application, ApplicationWindow, EventControllerFocus, EventControllerKey,
-    StackTransitionType,
+    StackTransitionType, Widget,
 };
 use gtk4::{Builder, Stack};
 use gtk4_layer_shell::{Edge, Layer, LayerShell};
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use crate::actions::applaunch::OPEN_WITH;
+use crate::api::events::SherlockEvent;
 use crate::daemon::daemon::close_response;
 use crate::launcher::emoji_picker::emojies;
 use crate::utils::config::SherlockConfig;
@@ -52,7 +54,7 @@ pub fn window(
     window.set_layer(Layer::Overlay);
     window.set_keyboard_mode(gtk4_layer_shell::KeyboardMode::Exclusive);
 
-    if !config.expand.enable {
+    if !config.expand.enable && config.runtime.lines.is_none() {
         window.set_default_height(height);
     } else {
         window.set_anchor(gtk4_layer_shell::Edge::Top, true);
@@ -88,6 +90,15 @@ pub fn window(
     });
     window.add_controller(key_controller);
 
+    // Notify subscribers about visibility changes
+    window.connect_show(|_| SherlockEvent::WindowShown.emit());
+    window.connect_hide(|_| {
+        if let Ok(mut files) = OPEN_WITH.lock() {
+            files.clear();
+        }
+        SherlockEvent::WindowHidden.emit()
+    });
+
     // Make backdrop if config key is set
     let backdrop = if let Some(c) = CONFIG.get() {
         if c.backdrop.enable {
@@ -243,6 +254,20 @@ pub fn window(
     return (window, stack, current_stack_page, win_ref);
 }
 
+/// Requests a startup notification token for an application about to be launched. On Wayland,
+/// GDK obtains it through `xdg_activation_v1` using the surface that currently has keyboard
+/// focus, which lets focus-stealing-prevention compositors raise the launched window. Has to be
+/// called before Sherlock's window gets hidden.
+pub fn activation_token<T: IsA<Widget>>(widget: &T) -> Option<String> {
+    let context = widget.display().app_launch_context();
+    let info =
+        AppInfo::create_from_commandline("sherlock", Some("Sherlock"), AppInfoCreateFlags::NONE)
+            .ok()?;
+    context
+        .startup_notify_id(Some(&info), &[])
+        .map(|token| token.to_string())
+}
+
 fn make_backdrop(
     application: &Application,
     main_window: &ApplicationWindow,
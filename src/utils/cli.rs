@@ -0,0 +1,544 @@
+// The command line interface of Sherlock. This file only depends on std, since the build
+// script includes it along with `cli_assets.rs` to generate shell completions and the man page.
+use std::path::PathBuf;
+
+/// How a flag consumes its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Switch,
+    Path,
+    Text,
+    Number,
+    /// One of the listed values
+    Choice(&'static [&'static str]),
+}
+
+pub struct FlagSpec {
+    pub long: &'static str,
+    pub short: Option<char>,
+    pub value: ValueKind,
+    pub section: &'static str,
+    pub help: &'static str,
+}
+
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub help: &'static str,
+    pub min_args: usize,
+    pub max_args: Option<usize>,
+}
+
+const fn flag(
+    long: &'static str,
+    value: ValueKind,
+    section: &'static str,
+    help: &'static str,
+) -> FlagSpec {
+    FlagSpec {
+        long,
+        short: None,
+        value,
+        section,
+        help,
+    }
+}
+
+pub const FLAGS: &[FlagSpec] = &[
+    // Basics
+    FlagSpec {
+        long: "--help",
+        short: Some('h'),
+        value: ValueKind::Switch,
+        section: "Basics",
+        help: "Show this help message with allowed flags.",
+    },
+    flag(
+        "--version",
+        ValueKind::Switch,
+        "Basics",
+        "Print the version of the application.",
+    ),
+    // Files
+    flag(
+        "--config",
+        ValueKind::Path,
+        "Files",
+        "Specify the configuration file to load.",
+    ),
+    flag(
+        "--fallback",
+        ValueKind::Path,
+        "Files",
+        "Specify the fallback file to load.",
+    ),
+    flag(
+        "--style",
+        ValueKind::Path,
+        "Files",
+        "Set the style configuration file.",
+    ),
+    flag(
+        "--ignore",
+        ValueKind::Path,
+        "Files",
+        "Specify the sherlock ignore file.",
+    ),
+    flag(
+        "--alias",
+        ValueKind::Path,
+        "Files",
+        "Specify the sherlock alias file (.json).",
+    ),
+    flag(
+        "--cache",
+        ValueKind::Path,
+        "Files",
+        "Specify the sherlock cache file (.json).",
+    ),
+    flag(
+        "--socket",
+        ValueKind::Path,
+        "Files",
+        "Specify the daemon socket. Defaults to $XDG_RUNTIME_DIR/sherlock/daemon.socket.",
+    ),
+    // Behavior
+    flag(
+        "--daemonize",
+        ValueKind::Switch,
+        "Behavior",
+        "Run sherlock in daemon mode.",
+    ),
+    flag(
+        "--sub-menu",
+        ValueKind::Text,
+        "Behavior",
+        "Start sherlock with an alias active already, for example 'pm' for the power menu.",
+    ),
+    flag(
+        "--photo",
+        ValueKind::Switch,
+        "Behavior",
+        "Keep sherlock open when it loses focus.",
+    ),
+    // Pipe mode
+    flag(
+        "--display-raw",
+        ValueKind::Switch,
+        "Pipe Mode",
+        "Force sherlock to use a singular tile to display the piped content.",
+    ),
+    flag(
+        "--center",
+        ValueKind::Switch,
+        "Pipe Mode",
+        "Center the piped content. Only works with --display-raw.",
+    ),
+    flag(
+        "--method",
+        ValueKind::Text,
+        "Pipe Mode",
+        "Specify what to do with the selected data row.",
+    ),
+    flag(
+        "--field",
+        ValueKind::Text,
+        "Pipe Mode",
+        "Specify which of your fields should be printed on return press.",
+    ),
+    flag(
+        "--multi",
+        ValueKind::Switch,
+        "Pipe Mode",
+        "Allow selecting multiple rows.",
+    ),
+    flag(
+        "--output-format",
+        ValueKind::Choice(&["json", "index", "tsv"]),
+        "Pipe Mode",
+        "Print the whole selected element, including hidden fields and the key that selected it.",
+    ),
+    flag(
+        "--bind",
+        ValueKind::Text,
+        "Pipe Mode",
+        "Add a key that selects the row, as KEY=ACTION[:EXIT_CODE]. Can be repeated.",
+    ),
+    flag(
+        "--preview-cmd",
+        ValueKind::Text,
+        "Pipe Mode",
+        "Show the output of a shell command for the selected row. {} is replaced by its result.",
+    ),
+    // Dmenu
+    flag(
+        "--dmenu",
+        ValueKind::Switch,
+        "Dmenu",
+        "Read lines from stdin and print the selected one, like dmenu.",
+    ),
+    flag(
+        "-dmenu",
+        ValueKind::Switch,
+        "Dmenu",
+        "Alias of --dmenu for rofi scripts.",
+    ),
+    flag(
+        "-p",
+        ValueKind::Text,
+        "Dmenu",
+        "Prompt shown in place of the mode title.",
+    ),
+    flag(
+        "-i",
+        ValueKind::Switch,
+        "Dmenu",
+        "Match case-insensitively. Dmenu mode is case-sensitive by default.",
+    ),
+    flag("-l", ValueKind::Number, "Dmenu", "Show this many rows."),
+    flag(
+        "-format",
+        ValueKind::Choice(&["s", "i", "d"]),
+        "Dmenu",
+        "Print the selected text (s), its index (i) or its index starting at 1 (d).",
+    ),
+    flag(
+        "-selected-row",
+        ValueKind::Number,
+        "Dmenu",
+        "Select the row with this index at startup.",
+    ),
+];
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "init",
+        usage: "[DIR]",
+        help: "Write default configs into DIR. Defaults to ~/.config/sherlock/.",
+        min_args: 0,
+        max_args: Some(1),
+    },
+    CommandSpec {
+        name: "msg",
+        usage: "<CALL> [ARGS]...",
+        help: "Send an api call to the running instance and print its reply.",
+        min_args: 1,
+        max_args: None,
+    },
+    CommandSpec {
+        name: "check",
+        usage: "",
+        help: "Validate the config and print all errors and warnings.",
+        min_args: 0,
+        max_args: Some(0),
+    },
+    CommandSpec {
+        name: "query",
+        usage: "<TEXT>...",
+        help: "Search in the running instance and print the matching rows.",
+        min_args: 1,
+        max_args: None,
+    },
+];
+
+/// The calls understood by `sherlock msg`
+pub const MSG_CALLS: &[&str] = &[
+    "show",
+    "close",
+    "clear",
+    "reload",
+    "input-only",
+    "clear-awaiting",
+    "activate-selected",
+    "obfuscate",
+    "set-query",
+    "bind",
+    "select-index",
+    "switch-mode",
+    "pipe",
+    "append-pipe",
+    "display-raw",
+    "open-with",
+    "get-mode",
+    "get-query",
+    "list-modes",
+    "list-rows",
+    "get-selected",
+];
+
+/// Flags that were removed, along with what to use instead
+const REMOVED_FLAGS: &[(&str, &str)] = &[(
+    "--time-inspect",
+    "Set the TIMING environment variable instead, e.g. `TIMING=true sherlock`.",
+)];
+
+pub const SECTIONS: &[&str] = &["Basics", "Files", "Behavior", "Pipe Mode", "Dmenu"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlagValue {
+    Switch,
+    Path(PathBuf),
+    Text(String),
+    Number(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubCommand {
+    Init(Option<PathBuf>),
+    Msg(Vec<String>),
+    Check,
+    Query(String),
+}
+
+#[derive(Debug, Default)]
+pub struct ParsedArgs {
+    pub command: Option<SubCommand>,
+    values: Vec<(&'static str, FlagValue)>,
+}
+impl ParsedArgs {
+    pub fn switch(&self, long: &str) -> bool {
+        self.values.iter().any(|(name, _)| *name == long)
+    }
+    pub fn path(&self, long: &str) -> Option<PathBuf> {
+        self.values.iter().find_map(|(name, value)| match value {
+            FlagValue::Path(path) if *name == long => Some(path.clone()),
+            _ => None,
+        })
+    }
+    pub fn text(&self, long: &str) -> Option<String> {
+        self.values.iter().find_map(|(name, value)| match value {
+            FlagValue::Text(text) if *name == long => Some(text.clone()),
+            _ => None,
+        })
+    }
+    /// All values of a flag that can be given more than once
+    pub fn texts(&self, long: &str) -> Vec<String> {
+        self.values
+            .iter()
+            .filter_map(|(name, value)| match value {
+                FlagValue::Text(text) if *name == long => Some(text.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    pub fn number(&self, long: &str) -> Option<u32> {
+        self.values.iter().find_map(|(name, value)| match value {
+            FlagValue::Number(number) if *name == long => Some(*number),
+            _ => None,
+        })
+    }
+    /// Whether dmenu mode was requested by either spelling of the flag
+    pub fn dmenu(&self) -> bool {
+        self.switch("--dmenu") || self.switch("-dmenu")
+    }
+}
+
+/// Parses the arguments following the program name. Flags are accepted anywhere, either as
+/// `--flag value` or `--flag=value`. Everything after `--` is passed to the subcommand.
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<ParsedArgs, String> {
+    let mut parsed = ParsedArgs::default();
+    let mut command: Option<&CommandSpec> = None;
+    let mut positional: Vec<String> = Vec::new();
+    let mut args = args.into_iter();
+    let mut raw = false;
+
+    while let Some(arg) = args.next() {
+        if raw || !arg.starts_with('-') || arg == "-" {
+            match command {
+                Some(_) => positional.push(arg),
+                None => {
+                    command = Some(
+                        COMMANDS
+                            .iter()
+                            .find(|c| c.name == arg)
+                            .ok_or_else(|| format!("Unknown command '{}'", arg))?,
+                    )
+                }
+            }
+            continue;
+        }
+        if arg == "--" {
+            raw = true;
+            continue;
+        }
+
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        if let Some((_, hint)) = REMOVED_FLAGS.iter().find(|(flag, _)| *flag == name) {
+            return Err(format!("Flag '{}' was removed. {}", name, hint));
+        }
+        let spec = FLAGS
+            .iter()
+            .find(|f| f.long == name || f.short.is_some_and(|s| name == format!("-{}", s)))
+            .ok_or_else(|| format!("Unknown flag '{}'", name))?;
+        let value = match spec.value {
+            ValueKind::Switch => {
+                if inline.is_some() {
+                    return Err(format!("Flag '{}' does not take a value", spec.long));
+                }
+                FlagValue::Switch
+            }
+            kind => {
+                let value = inline
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Flag '{}' expects a value", spec.long))?;
+                match kind {
+                    ValueKind::Path => FlagValue::Path(PathBuf::from(value)),
+                    ValueKind::Number => FlagValue::Number(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Flag '{}' expects a number", spec.long))?,
+                    ),
+                    ValueKind::Choice(choices) if !choices.contains(&value.as_str()) => {
+                        return Err(format!(
+                            "Flag '{}' expects one of: {}",
+                            spec.long,
+                            choices.join(", ")
+                        ));
+                    }
+                    _ => FlagValue::Text(value),
+                }
+            }
+        };
+        parsed.values.push((spec.long, value));
+    }
+
+    if !parsed.dmenu() {
+        let dmenu_only = parsed.values.iter().find(|(name, _)| {
+            FLAGS
+                .iter()
+                .any(|f| f.long == *name && f.section == "Dmenu")
+        });
+        if let Some((name, _)) = dmenu_only {
+            return Err(format!("Flag '{}' is only supported with --dmenu", name));
+        }
+    }
+
+    if let Some(spec) = command {
+        let count = positional.len();
+        if count < spec.min_args || spec.max_args.is_some_and(|max| count > max) {
+            return Err(format!("Usage: sherlock {} {}", spec.name, spec.usage));
+        }
+        parsed.command = Some(match spec.name {
+            "init" => SubCommand::Init(positional.pop().map(PathBuf::from)),
+            "msg" => SubCommand::Msg(positional),
+            "check" => SubCommand::Check,
+            _ => SubCommand::Query(positional.join(" ")),
+        });
+    }
+    Ok(parsed)
+}
+
+pub fn flag_usage(spec: &FlagSpec) -> String {
+    let names = match spec.short {
+        Some(short) => format!("-{}, {}", short, spec.long),
+        None => spec.long.to_string(),
+    };
+    match spec.value {
+        ValueKind::Switch => names,
+        ValueKind::Path => format!("{} <PATH>", names),
+        ValueKind::Text => format!("{} <VALUE>", names),
+        ValueKind::Number => format!("{} <N>", names),
+        ValueKind::Choice(choices) => format!("{} <{}>", names, choices.join("|")),
+    }
+}
+
+pub fn help() -> String {
+    let mut out = String::from("Usage: sherlock [FLAGS] [COMMAND]\n\nCOMMANDS:\n");
+    for command in COMMANDS {
+        let usage = format!("{} {}", command.name, command.usage);
+        out.push_str(&format!("  {:<24} {}\n", usage, command.help));
+    }
+    for section in SECTIONS {
+        out.push_str(&format!("\n{}:\n", section.to_uppercase()));
+        for spec in FLAGS.iter().filter(|f| f.section == *section) {
+            out.push_str(&format!("  {:<24} {}\n", flag_usage(spec), spec.help));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<ParsedArgs, String> {
+        parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn parses_flags_and_subcommands() {
+        let parsed = parse_args(&["--daemonize", "--config=~/a.toml", "--sub-menu", "pm"]).unwrap();
+        assert!(parsed.switch("--daemonize"));
+        assert!(!parsed.switch("--photo"));
+        assert_eq!(parsed.path("--config"), Some(PathBuf::from("~/a.toml")));
+        assert_eq!(parsed.text("--sub-menu").as_deref(), Some("pm"));
+        assert_eq!(parsed.command, None);
+
+        let parsed = parse_args(&["--socket", "/tmp/s", "msg", "set-query", "--", "--x"]).unwrap();
+        assert_eq!(parsed.path("--socket"), Some(PathBuf::from("/tmp/s")));
+        assert_eq!(
+            parsed.command,
+            Some(SubCommand::Msg(vec!["set-query".into(), "--x".into()]))
+        );
+
+        // `init` is only a command in command position
+        let parsed = parse_args(&["--sub-menu", "init"]).unwrap();
+        assert_eq!(parsed.command, None);
+        let parsed = parse_args(&["init", "/tmp/conf"]).unwrap();
+        assert_eq!(
+            parsed.command,
+            Some(SubCommand::Init(Some(PathBuf::from("/tmp/conf"))))
+        );
+        let parsed = parse_args(&["query", "fire", "fox"]).unwrap();
+        assert_eq!(parsed.command, Some(SubCommand::Query("fire fox".into())));
+        assert!(parse_args(&["-h"]).unwrap().switch("--help"));
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        assert!(parse_args(&["--time-inspect"])
+            .unwrap_err()
+            .contains("TIMING=true"));
+        assert!(parse_args(&["--config"]).is_err());
+        assert!(parse_args(&["--daemonize=yes"]).is_err());
+        assert!(parse_args(&["launch"]).is_err());
+        assert!(parse_args(&["msg"]).is_err());
+        assert!(parse_args(&["check", "extra"]).is_err());
+        assert!(parse_args(&["init", "a", "b"]).is_err());
+        assert!(parse_args(&["-p", "Run"]).is_err());
+        assert!(parse_args(&["--dmenu", "-l", "ten"]).is_err());
+        assert!(parse_args(&["--dmenu", "-format", "x"]).is_err());
+    }
+
+    #[test]
+    fn parses_dmenu_flags() {
+        let parsed =
+            parse_args(&["-dmenu", "-i", "-p", "Run:", "-l", "10", "-format", "i"]).unwrap();
+        assert!(parsed.dmenu());
+        assert!(parsed.switch("-i"));
+        assert_eq!(parsed.text("-p").as_deref(), Some("Run:"));
+        assert_eq!(parsed.number("-l"), Some(10));
+        assert_eq!(parsed.text("-format").as_deref(), Some("i"));
+        assert_eq!(parsed.number("-selected-row"), None);
+
+        let parsed =
+            parse_args(&["--output-format", "json", "--dmenu", "-selected-row=2"]).unwrap();
+        assert_eq!(parsed.text("--output-format").as_deref(), Some("json"));
+        assert_eq!(parsed.number("-selected-row"), Some(2));
+
+        let parsed = parse_args(&["--bind", "alt-1=delete:10", "--bind=alt-e=edit"]).unwrap();
+        assert_eq!(
+            parsed.texts("--bind"),
+            vec!["alt-1=delete:10", "alt-e=edit"]
+        );
+
+        let parsed = parse_args(&["--preview-cmd", "head -n 20 {}"]).unwrap();
+        assert_eq!(
+            parsed.text("--preview-cmd").as_deref(),
+            Some("head -n 20 {}")
+        );
+    }
+}
@@ -0,0 +1,230 @@
+// Shell completions and the man page, generated from the flags in `cli.rs`. Only the build
+// script and the tests use this file.
+use super::cli::{flag_usage, ValueKind, COMMANDS, FLAGS, MSG_CALLS, SECTIONS};
+
+fn flags_of(kinds: &[ValueKind]) -> Vec<&'static str> {
+    FLAGS
+        .iter()
+        .filter(|f| kinds.contains(&f.value))
+        .map(|f| f.long)
+        .collect()
+}
+
+pub fn bash_completion() -> String {
+    let mut words: Vec<String> = COMMANDS.iter().map(|c| c.name.to_string()).collect();
+    for spec in FLAGS {
+        words.push(spec.long.to_string());
+        if let Some(short) = spec.short {
+            words.push(format!("-{}", short));
+        }
+    }
+    let choices: String = FLAGS
+        .iter()
+        .filter_map(|f| match f.value {
+            ValueKind::Choice(choices) => Some(format!(
+                "        {})\n            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n            return\n            ;;\n",
+                f.long,
+                choices.join(" ")
+            )),
+            _ => None,
+        })
+        .collect();
+    format!(
+        r#"_sherlock() {{
+    local cur prev
+    cur="${{COMP_WORDS[COMP_CWORD]}}"
+    prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    case "$prev" in
+        {paths})
+            COMPREPLY=($(compgen -f -- "$cur"))
+            return
+            ;;
+        {texts})
+            return
+            ;;
+{choices}        msg)
+            COMPREPLY=($(compgen -W "{calls}" -- "$cur"))
+            return
+            ;;
+        init)
+            COMPREPLY=($(compgen -d -- "$cur"))
+            return
+            ;;
+    esac
+    COMPREPLY=($(compgen -W "{words}" -- "$cur"))
+}}
+complete -F _sherlock sherlock
+"#,
+        paths = flags_of(&[ValueKind::Path]).join("|"),
+        texts = flags_of(&[ValueKind::Text, ValueKind::Number]).join("|"),
+        choices = choices,
+        calls = MSG_CALLS.join(" "),
+        words = words.join(" "),
+    )
+}
+
+pub fn zsh_completion() -> String {
+    fn escape(text: &str) -> String {
+        text.replace('\'', r"'\''")
+            .replace('[', r"\[")
+            .replace(']', r"\]")
+            .replace(':', r"\:")
+    }
+    let mut out = String::from("#compdef sherlock\n\nlocal -a commands\ncommands=(\n");
+    for command in COMMANDS {
+        out.push_str(&format!(
+            "    '{}:{}'\n",
+            command.name,
+            escape(command.help)
+        ));
+    }
+    out.push_str(")\n\n_arguments \\\n");
+    for spec in FLAGS {
+        let action = match spec.value {
+            ValueKind::Switch => String::new(),
+            ValueKind::Path => String::from(":path:_files"),
+            ValueKind::Text => String::from(":value: "),
+            ValueKind::Number => String::from(":number: "),
+            ValueKind::Choice(choices) => format!(":value:({})", choices.join(" ")),
+        };
+        let names = match spec.short {
+            Some(short) => format!(
+                "(-{short} {long})'{{-{short},{long}}}'",
+                short = short,
+                long = spec.long
+            ),
+            None => spec.long.to_string(),
+        };
+        out.push_str(&format!(
+            "    '{}[{}]{}' \\\n",
+            names,
+            escape(spec.help),
+            action
+        ));
+    }
+    out.push_str(&format!(
+        r#"    '1: :->command' \
+    '*:: :->args'
+
+case $state in
+    command)
+        _describe 'command' commands
+        ;;
+    args)
+        case $words[1] in
+            msg) (( CURRENT == 2 )) && _values 'call' {calls} ;;
+            init) _files -/ ;;
+        esac
+        ;;
+esac
+"#,
+        calls = MSG_CALLS.join(" ")
+    ));
+    out
+}
+
+pub fn fish_completion() -> String {
+    fn escape(text: &str) -> String {
+        text.replace('\\', r"\\").replace('\'', r"\'")
+    }
+    let names: Vec<&str> = COMMANDS.iter().map(|c| c.name).collect();
+    let mut out = String::from("complete -c sherlock -f\n");
+    for command in COMMANDS {
+        out.push_str(&format!(
+            "complete -c sherlock -n 'not __fish_seen_subcommand_from {}' -a {} -d '{}'\n",
+            names.join(" "),
+            command.name,
+            escape(command.help)
+        ));
+    }
+    out.push_str(&format!(
+        "complete -c sherlock -n '__fish_seen_subcommand_from msg' -a '{}'\n",
+        MSG_CALLS.join(" ")
+    ));
+    out.push_str("complete -c sherlock -n '__fish_seen_subcommand_from init' -a '(__fish_complete_directories)'\n");
+    for spec in FLAGS {
+        // Single-dash flags like `-dmenu` are old-style options in fish
+        let mut line = match spec.long.strip_prefix("--") {
+            Some(long) => format!("complete -c sherlock -l {}", long),
+            None => format!("complete -c sherlock -o {}", &spec.long[1..]),
+        };
+        if let Some(short) = spec.short {
+            line.push_str(&format!(" -s {}", short));
+        }
+        match spec.value {
+            ValueKind::Switch => {}
+            ValueKind::Path => line.push_str(" -r -F"),
+            ValueKind::Text | ValueKind::Number => line.push_str(" -r"),
+            ValueKind::Choice(choices) => line.push_str(&format!(" -r -a '{}'", choices.join(" "))),
+        }
+        line.push_str(&format!(" -d '{}'\n", escape(spec.help)));
+        out.push_str(&line);
+    }
+    out
+}
+
+pub fn man_page(version: &str) -> String {
+    fn escape(text: &str) -> String {
+        text.replace('\\', r"\e").replace('-', r"\-")
+    }
+    let mut out = format!(
+        ".TH SHERLOCK 1 \"\" \"sherlock {}\" \"User Commands\"\n\
+         .SH NAME\nsherlock \\- application launcher for Wayland\n\
+         .SH SYNOPSIS\n.B sherlock\n[\\fIFLAGS\\fR] [\\fICOMMAND\\fR]\n\
+         .SH COMMANDS\n",
+        version
+    );
+    for command in COMMANDS {
+        out.push_str(&format!(
+            ".TP\n\\fB{}\\fR {}\n{}\n",
+            command.name,
+            escape(command.usage),
+            escape(command.help)
+        ));
+    }
+    out.push_str(".SH FLAGS\n");
+    for section in SECTIONS {
+        out.push_str(&format!(".SS {}\n", section));
+        for spec in FLAGS.iter().filter(|f| f.section == *section) {
+            out.push_str(&format!(
+                ".TP\n\\fB{}\\fR\n{}\n",
+                escape(&flag_usage(spec)),
+                escape(spec.help)
+            ));
+        }
+    }
+    out.push_str(
+        ".SH SEE ALSO\nhttps://github.com/Skxxtz/sherlock/blob/documentation/docs/flags.md\n",
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::cli::help;
+
+    #[test]
+    fn generated_files_cover_all_flags() {
+        let outputs = [
+            help(),
+            bash_completion(),
+            zsh_completion(),
+            fish_completion(),
+            man_page("0.0.0"),
+        ];
+        for output in outputs {
+            for command in COMMANDS {
+                assert!(output.contains(command.name));
+            }
+            for spec in FLAGS {
+                let name = spec.long.trim_start_matches('-');
+                assert!(
+                    output.contains(name) || output.contains(&name.replace('-', r"\-")),
+                    "{} is missing",
+                    spec.long
+                );
+            }
+        }
+    }
+}
@@ -4,9 +4,11 @@ use std::{
     env, fs,
     path::{Path, PathBuf},
     process::Command,
+    sync::RwLock,
 };
 
 use super::{
+    cli::SubCommand,
     errors::{SherlockError, SherlockErrorType},
     files::{expand_path, home_dir},
 };
@@ -27,8 +29,48 @@ pub struct SherlockFlags {
     pub field: Option<String>,
     pub sub_menu: Option<String>,
     pub multi: bool,
+    pub output_format: Option<String>,
+    pub binds: Vec<PipeBind>,
+    pub preview_cmd: Option<String>,
     pub photo_mode: bool,
+    pub socket: Option<PathBuf>,
+    pub dmenu: bool,
+    pub prompt: Option<String>,
+    pub case_insensitive: bool,
+    pub lines: Option<u32>,
+    pub format: Option<String>,
+    pub selected_row: Option<u32>,
+    pub command: Option<SubCommand>,
+}
+/// Holds the active [`SherlockConfig`]. Works like a `OnceLock`, except that the configuration
+/// can be swapped at runtime using [`ConfigCell::replace`]. Replaced configurations are leaked
+/// because references to them may still be held.
+pub struct ConfigCell {
+    inner: RwLock<Option<&'static SherlockConfig>>,
+}
+impl ConfigCell {
+    pub const fn new() -> Self {
+        Self {
+            inner: RwLock::new(None),
+        }
+    }
+    pub fn get(&self) -> Option<&'static SherlockConfig> {
+        *self.inner.read().unwrap()
+    }
+    /// Sets the configuration if none is set yet
+    pub fn set(&self, config: SherlockConfig) -> Result<(), SherlockConfig> {
+        let mut inner = self.inner.write().unwrap();
+        if inner.is_some() {
+            return Err(config);
+        }
+        *inner = Some(Box::leak(Box::new(config)));
+        Ok(())
+    }
+    pub fn replace(&self, config: SherlockConfig) {
+        *self.inner.write().unwrap() = Some(Box::leak(Box::new(config)));
+    }
 }
+
 /// Configuration sections:
 ///
 /// - **default_apps**: User-defined default applications (e.g., terminal, calendar).
@@ -91,13 +133,7 @@ impl SherlockConfig {
             behavior: ConfigBehavior::default(),
             binds: ConfigBinds::default(),
             files: ConfigFiles::default(),
-            runtime: Runtime {
-                method: None,
-                multi: false,
-                center: false,
-                photo_mode: false,
-                display_raw: false,
-            },
+            runtime: Runtime::default(),
             expand: ConfigExpand::default(),
             backdrop: ConfigBackdrop::default(),
         }
@@ -111,13 +147,7 @@ impl SherlockConfig {
             behavior: ConfigBehavior::default(),
             binds: ConfigBinds::default(),
             files: ConfigFiles::with_root(root),
-            runtime: Runtime {
-                method: None,
-                multi: false,
-                center: false,
-                photo_mode: false,
-                display_raw: false,
-            },
+            runtime: Runtime::default(),
             expand: ConfigExpand::default(),
             backdrop: ConfigBackdrop::default(),
         }
@@ -402,10 +432,19 @@ impl SherlockConfig {
         config.runtime.method = sherlock_flags.method.clone();
         config.runtime.center = sherlock_flags.center_raw.clone();
         config.runtime.multi = sherlock_flags.multi;
+        config.runtime.output_format = sherlock_flags.output_format.clone();
+        config.runtime.binds = sherlock_flags.binds.clone();
+        config.runtime.preview_cmd = sherlock_flags.preview_cmd.clone();
         config.runtime.display_raw = sherlock_flags.display_raw;
         config.runtime.photo_mode = sherlock_flags.photo_mode;
         config.behavior.field = sherlock_flags.field.clone();
 
+        config.runtime.dmenu = sherlock_flags.dmenu;
+        config.runtime.prompt = sherlock_flags.prompt.clone();
+        config.runtime.case_sensitive = sherlock_flags.dmenu && !sherlock_flags.case_insensitive;
+        config.runtime.lines = sherlock_flags.lines;
+        config.runtime.format = sherlock_flags.format.clone();
+
         if sherlock_flags.daemonize {
             config.behavior.daemonize = true;
         }
@@ -509,6 +548,10 @@ pub struct ConfigAppearance {
     pub search_bar_icon_back: String,
     #[serde(default = "default_icon_size")]
     pub search_icon_size: i32,
+    #[serde(default)]
+    pub subtitles: bool,
+    #[serde(default)]
+    pub preview: bool,
 }
 impl ConfigAppearance {
     fn with_root(root: &PathBuf) -> Self {
@@ -553,6 +596,8 @@ impl Default for ConfigAppearance {
             search_bar_icon: default_search_icon(),
             search_bar_icon_back: default_search_icon_back(),
             search_icon_size: default_icon_size(),
+            subtitles: false,
+            preview: false,
         }
     }
 }
@@ -575,6 +620,10 @@ pub struct ConfigBehavior {
     pub global_flags: Option<String>,
     #[serde(default)]
     pub sub_menu: Option<String>,
+    #[serde(default = "default_frecency_half_life")]
+    pub frecency_half_life: f32,
+    #[serde(default = "default_launch_backend")]
+    pub launch_backend: String,
 }
 impl Default for ConfigBehavior {
     fn default() -> Self {
@@ -587,6 +636,8 @@ impl Default for ConfigBehavior {
             global_prefix: None,
             global_flags: None,
             sub_menu: None,
+            frecency_half_life: default_frecency_half_life(),
+            launch_backend: default_launch_backend(),
         }
     }
 }
@@ -657,6 +708,35 @@ pub struct ConfigBinds {
     pub exec_inplace: Option<String>,
 }
 
+/// A key binding that selects the current row in pipe mode. The action is reported as the
+/// trigger of the selection and the exit code is returned once Sherlock closes.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct PipeBind {
+    pub key: String,
+    #[serde(default)]
+    pub action: Option<String>,
+    #[serde(default)]
+    pub exit: Option<i32>,
+}
+impl PipeBind {
+    /// Parses a binding written as `KEY=ACTION`, `KEY=ACTION:EXIT_CODE` or `KEY=:EXIT_CODE`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (key, rest) = spec.split_once('=')?;
+        let (action, exit) = match rest.rsplit_once(':') {
+            Some((action, code)) => (action, Some(code.parse().ok()?)),
+            None => (rest, None),
+        };
+        if key.is_empty() || (action.is_empty() && exit.is_none()) {
+            return None;
+        }
+        Some(Self {
+            key: key.to_string(),
+            action: (!action.is_empty()).then(|| action.to_string()),
+            exit,
+        })
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct Runtime {
     #[serde(default)]
@@ -665,6 +745,15 @@ pub struct Runtime {
     #[serde(default)]
     pub multi: bool,
 
+    #[serde(default)]
+    pub output_format: Option<String>,
+
+    #[serde(default)]
+    pub binds: Vec<PipeBind>,
+
+    #[serde(default)]
+    pub preview_cmd: Option<String>,
+
     #[serde(default)]
     pub center: bool,
 
@@ -673,6 +762,21 @@ pub struct Runtime {
 
     #[serde(default)]
     pub display_raw: bool,
+
+    #[serde(default)]
+    pub dmenu: bool,
+
+    #[serde(default)]
+    pub prompt: Option<String>,
+
+    #[serde(default)]
+    pub case_sensitive: bool,
+
+    #[serde(default)]
+    pub lines: Option<u32>,
+
+    #[serde(default)]
+    pub format: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -763,6 +867,12 @@ pub fn default_actions() -> PathBuf {
     PathBuf::from("~/.config/sherlock/sherlock_actions.json")
 }
 
+pub fn default_frecency_half_life() -> f32 {
+    7.0
+}
+pub fn default_launch_backend() -> String {
+    String::from("default")
+}
 pub fn default_true() -> bool {
     true
 }
@@ -857,3 +967,26 @@ fn is_terminal_installed(terminal: &str) -> bool {
         .output()
         .is_ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pipe_binds() {
+        let bind = PipeBind::parse("alt-1=delete:10").unwrap();
+        assert_eq!(bind.key, "alt-1");
+        assert_eq!(bind.action.as_deref(), Some("delete"));
+        assert_eq!(bind.exit, Some(10));
+
+        let bind = PipeBind::parse("alt-e=edit").unwrap();
+        assert_eq!((bind.action.as_deref(), bind.exit), (Some("edit"), None));
+        let bind = PipeBind::parse("alt-d=:11").unwrap();
+        assert_eq!((bind.action, bind.exit), (None, Some(11)));
+
+        assert!(PipeBind::parse("alt-1").is_none());
+        assert!(PipeBind::parse("=delete").is_none());
+        assert!(PipeBind::parse("alt-1=").is_none());
+        assert!(PipeBind::parse("alt-1=delete:ten").is_none());
+    }
+}
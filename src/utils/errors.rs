@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     api::call::ApiCall, daemon::daemon::SizedMessage, g_subclasses::sherlock_row::SherlockRow,
-    ui::tiles::error_tile::ErrorTile, SOCKET_PATH,
+    runtime_paths, ui::tiles::error_tile::ErrorTile,
 };
 
 #[macro_export]
@@ -53,9 +53,10 @@ impl SherlockError {
         object
     }
     pub fn insert(self, is_error: bool) -> Result<(), SherlockError> {
-        let mut stream = UnixStream::connect(SOCKET_PATH).map_err(|e| {
+        let socket = runtime_paths().socket.as_str();
+        let mut stream = UnixStream::connect(socket).map_err(|e| {
             sherlock_error!(
-                SherlockErrorType::SocketConnectError(SOCKET_PATH.to_string()),
+                SherlockErrorType::SocketConnectError(socket.to_string()),
                 e.to_string()
             )
         })?;
@@ -105,6 +106,7 @@ pub enum SherlockErrorType {
 
     // Regex / Parsing
     RegexError(String),
+    ExecParseError(String),
 
     // Commands
     CommandExecutionError(String),
@@ -121,6 +123,7 @@ pub enum SherlockErrorType {
     SocketRemoveError(String),
     SocketConnectError(String),
     SocketWriteError(String),
+    SocketReadError(String),
 
     // Sqlite
     SqlConnectionError(),
@@ -201,6 +204,9 @@ impl SherlockErrorType {
             SherlockErrorType::RegexError(key) => {
                 format!("Failed to compile the regular expression for \"{}\"", key)
             }
+            SherlockErrorType::ExecParseError(exec) => {
+                format!("Failed to parse the command line \"{}\"", exec)
+            }
 
             // Commands
             SherlockErrorType::CommandExecutionError(cmd) => {
@@ -225,6 +231,7 @@ impl SherlockErrorType {
             SherlockErrorType::SocketRemoveError(socket) => socket_msg("close", socket),
             SherlockErrorType::SocketConnectError(socket) => socket_msg("connect", socket),
             SherlockErrorType::SocketWriteError(socket) => socket_msg("send message to", socket),
+            SherlockErrorType::SocketReadError(socket) => socket_msg("read message from", socket),
 
             // Sqlite
             SherlockErrorType::SqlConnectionError() => {
@@ -1,7 +1,9 @@
+use nix::unistd::getuid;
 use std::{
     env,
-    fs::File,
+    fs::{self, DirBuilder, File, Permissions},
     io::{self, BufRead},
+    os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt},
     path::{Path, PathBuf},
 };
 
@@ -36,3 +38,77 @@ pub fn home_dir() -> Result<PathBuf, SherlockError> {
         })
         .map(PathBuf::from)
 }
+
+/// Returns Sherlock's per-user runtime directory `$XDG_RUNTIME_DIR/sherlock/`, falling back to
+/// `/tmp/sherlock-<uid>/` if the variable is unset. The directory is created with 0700
+/// permissions and rejected if it belongs to another user.
+pub fn runtime_dir() -> Result<PathBuf, SherlockError> {
+    let uid = getuid();
+    let dir = match env::var_os("XDG_RUNTIME_DIR") {
+        Some(base) if !base.is_empty() => PathBuf::from(base).join("sherlock"),
+        _ => env::temp_dir().join(format!("sherlock-{}", uid)),
+    };
+    let dir_str = dir.to_string_lossy().to_string();
+    DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|e| {
+            sherlock_error!(
+                SherlockErrorType::DirCreateError(dir_str.clone()),
+                e.to_string()
+            )
+        })?;
+
+    let metadata = fs::symlink_metadata(&dir).map_err(|e| {
+        sherlock_error!(
+            SherlockErrorType::DirReadError(dir_str.clone()),
+            e.to_string()
+        )
+    })?;
+    if !metadata.is_dir() || metadata.uid() != uid.as_raw() {
+        return Err(sherlock_error!(
+            SherlockErrorType::DirReadError(dir_str),
+            "The runtime directory is not a directory owned by the current user."
+        ));
+    }
+    if metadata.mode() & 0o777 != 0o700 {
+        fs::set_permissions(&dir, Permissions::from_mode(0o700)).map_err(|e| {
+            sherlock_error!(
+                SherlockErrorType::DirCreateError(dir_str.clone()),
+                e.to_string()
+            )
+        })?;
+    }
+    Ok(dir)
+}
+
+/// Locations of the daemon socket, the pipe response socket and the lock file
+#[derive(Debug, Clone, Default)]
+pub struct RuntimePaths {
+    pub socket: String,
+    pub pipe_socket: String,
+    pub lock: String,
+}
+impl RuntimePaths {
+    /// Places all files inside the [`runtime_dir`]. If a socket is specified using the
+    /// `--socket` flag, the pipe socket and lock file are placed next to it instead, so
+    /// that several instances can run side by side.
+    pub fn new(socket: Option<PathBuf>) -> Result<Self, SherlockError> {
+        if let Some(socket) = socket {
+            let socket = socket.to_string_lossy().to_string();
+            return Ok(Self {
+                pipe_socket: format!("{}.pipe", socket),
+                lock: format!("{}.lock", socket),
+                socket,
+            });
+        }
+        let dir = runtime_dir()?;
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+        Ok(Self {
+            socket: path("daemon.socket"),
+            pipe_socket: path("pipe.socket"),
+            lock: path("sherlock.lock"),
+        })
+    }
+}
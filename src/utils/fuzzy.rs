@@ -0,0 +1,217 @@
+// Scores used by the fuzzy matcher. Loosely modeled after fzf's v2 algorithm.
+const SCORE_MATCH: i32 = 16;
+const SCORE_GAP_START: i32 = -3;
+const SCORE_GAP_EXTENSION: i32 = -1;
+
+const BONUS_START: i32 = 10;
+const BONUS_BOUNDARY: i32 = 8;
+const BONUS_CAMEL: i32 = 7;
+const BONUS_CONSECUTIVE: i32 = 5;
+const BONUS_FIRST_CHAR_MULTIPLIER: i32 = 2;
+
+/// Result of a successful fuzzy match
+/// * **score**: The higher the score, the better the match
+/// * **indices**: Character (not byte) positions inside the haystack that were matched
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i32,
+    pub indices: Vec<usize>,
+}
+
+/// Matches `query` as a case-insensitive subsequence of `haystack`.
+///
+/// Matches at the start of the haystack, after separators (word boundaries) and at camelCase
+/// humps score higher, as do runs of consecutive characters. Gaps between matched characters
+/// are penalized. Returns `None` if not every query character could be matched in order.
+pub fn fuzzy_match(query: &str, haystack: &str) -> Option<FuzzyMatch> {
+    fuzzy_match_with(query, haystack, false)
+}
+
+/// Like [`fuzzy_match`], but only matches characters of the same case if `case_sensitive` is
+/// set.
+pub fn fuzzy_match_with(query: &str, haystack: &str, case_sensitive: bool) -> Option<FuzzyMatch> {
+    let fold = |c: char| if case_sensitive { c } else { fold_case(c) };
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(fold)
+        .collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch::default());
+    }
+    let original: Vec<char> = haystack.chars().collect();
+    let folded: Vec<char> = original.iter().copied().map(fold).collect();
+    let (m, n) = (query.len(), folded.len());
+    if m > n {
+        return None;
+    }
+
+    // Cheap subsequence check before running the full scoring pass
+    let mut remaining = query.iter().peekable();
+    for c in folded.iter() {
+        if remaining.peek() == Some(&c) {
+            remaining.next();
+        }
+    }
+    if remaining.peek().is_some() {
+        return None;
+    }
+
+    let bonus: Vec<i32> = (0..n)
+        .map(|j| position_bonus(j.checked_sub(1).map(|p| original[p]), original[j]))
+        .collect();
+
+    // scores[i][j]: best score with query[i] matched at haystack[j]
+    // origin[i][j]: haystack position of query[i - 1] for that best score
+    let mut scores: Vec<Vec<Option<i32>>> = vec![vec![None; n]; m];
+    let mut origin: Vec<Vec<usize>> = vec![vec![0; n]; m];
+
+    for j in 0..n {
+        if folded[j] == query[0] {
+            scores[0][j] = Some(SCORE_MATCH + bonus[j] * BONUS_FIRST_CHAR_MULTIPLIER);
+        }
+    }
+    for i in 1..m {
+        // Best score of a previous match followed by a gap up to (excluding) j
+        let mut gap: Option<(i32, usize)> = None;
+        for j in i..n {
+            if j >= 2 {
+                if let Some((score, from)) = gap {
+                    gap = Some((score + SCORE_GAP_EXTENSION, from));
+                }
+                if let Some(prev) = scores[i - 1][j - 2] {
+                    let opened = prev + SCORE_GAP_START;
+                    if gap.map_or(true, |(score, _)| opened > score) {
+                        gap = Some((opened, j - 2));
+                    }
+                }
+            }
+            if folded[j] != query[i] {
+                continue;
+            }
+            let consecutive =
+                scores[i - 1][j - 1].map(|prev| (prev + bonus[j].max(BONUS_CONSECUTIVE), j - 1));
+            let gapped = gap.map(|(score, from)| (score + bonus[j], from));
+            let best = match (consecutive, gapped) {
+                (Some(c), Some(g)) => Some(if g.0 > c.0 { g } else { c }),
+                (c, g) => c.or(g),
+            };
+            if let Some((score, from)) = best {
+                scores[i][j] = Some(score + SCORE_MATCH);
+                origin[i][j] = from;
+            }
+        }
+    }
+
+    // Pick the best end position; on ties prefer the earliest one
+    let (score, mut j) = scores[m - 1]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, s)| s.map(|s| (s, j)))
+        .fold(None, |best: Option<(i32, usize)>, cur| match best {
+            Some(b) if b.0 >= cur.0 => Some(b),
+            _ => Some(cur),
+        })?;
+
+    let mut indices = vec![0; m];
+    for i in (0..m).rev() {
+        indices[i] = j;
+        if i > 0 {
+            j = origin[i][j];
+        }
+    }
+    Some(FuzzyMatch { score, indices })
+}
+
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn position_bonus(prev: Option<char>, current: char) -> i32 {
+    let prev = match prev {
+        Some(prev) => prev,
+        None => return BONUS_START,
+    };
+    if !current.is_alphanumeric() {
+        return 0;
+    }
+    if is_separator(prev) {
+        BONUS_BOUNDARY
+    } else if (prev.is_lowercase() && current.is_uppercase())
+        || (!prev.is_numeric() && current.is_numeric())
+    {
+        BONUS_CAMEL
+    } else {
+        0
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ';' | '-' | '_' | '.' | '/' | ',' | ':' | '(' | '[')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(query: &str, haystack: &str) -> i32 {
+        fuzzy_match(query, haystack).unwrap().score
+    }
+
+    #[test]
+    fn matches_acronyms() {
+        let result = fuzzy_match("vsc", "Visual Studio Code").unwrap();
+        assert_eq!(result.indices, vec![0, 7, 14]);
+        assert_eq!(
+            fuzzy_match("ds", "DevSettings").unwrap().indices,
+            vec![0, 3]
+        );
+    }
+
+    #[test]
+    fn ranks_boundaries_above_mid_word_matches() {
+        assert!(score("term", "Terminal") > score("term", "Alacritty Terminal"));
+        assert!(score("term", "Alacritty Terminal") > score("term", "Determinism"));
+        assert!(score("ff", "Firefox") > score("ff", "Jiffy"));
+        assert!(score("ds", "DevSettings") > score("ds", "Kids"));
+    }
+
+    #[test]
+    fn prefers_consecutive_characters() {
+        assert!(score("abc", "xabcx") > score("abc", "xaxbxcx"));
+        assert_eq!(fuzzy_match("ab", "xaxxab").unwrap().indices, vec![4, 5]);
+    }
+
+    #[test]
+    fn returns_character_indices() {
+        // "Ü" and "ü" are two bytes long, indices count characters
+        let result = fuzzy_match("bro", "Über Büro").unwrap();
+        assert_eq!(result.indices, vec![5, 7, 8]);
+        assert_eq!(fuzzy_match("übe", "Über").unwrap().indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn ignores_case() {
+        assert_eq!(fuzzy_match("ff", "FireFox").unwrap().indices, vec![0, 4]);
+        assert_eq!(fuzzy_match("FF", "firefox").unwrap().indices, vec![0, 4]);
+    }
+
+    #[test]
+    fn respects_case_sensitivity() {
+        assert!(fuzzy_match_with("ff", "FireFox", false).is_some());
+        assert!(fuzzy_match_with("ff", "FireFox", true).is_none());
+        assert_eq!(
+            fuzzy_match_with("FF", "FireFox", true).unwrap().indices,
+            vec![0, 4]
+        );
+    }
+
+    #[test]
+    fn rejects_missing_characters() {
+        assert!(fuzzy_match("xf", "Firefox").is_none());
+        assert!(fuzzy_match("firefoxes", "Firefox").is_none());
+        assert!(fuzzy_match("q", "").is_none());
+        assert_eq!(fuzzy_match("", "Firefox"), Some(FuzzyMatch::default()));
+        assert_eq!(fuzzy_match("  ", "Firefox"), Some(FuzzyMatch::default()));
+    }
+}
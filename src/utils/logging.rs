@@ -1,16 +1,25 @@
 use std::io::Write;
-use std::{env::home_dir, fs::OpenOptions, sync::Mutex};
+use std::{
+    env::home_dir,
+    fs::OpenOptions,
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+};
 
 use chrono::Local;
 use once_cell::sync::Lazy;
 
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
 static LOG_FILE: Lazy<Mutex<std::fs::File>> = Lazy::new(|| {
-    let home = home_dir().expect(&format!(
-        "{}:{} - Failed to find home directory.",
-        file!(),
-        line!()
-    ));
-    let location = home.join(".sherlock/sherlock.log");
+    let location = LOG_PATH.get().cloned().unwrap_or_else(|| {
+        let home = home_dir().expect(&format!(
+            "{}:{} - Failed to find home directory.",
+            file!(),
+            line!()
+        ));
+        home.join(".sherlock/sherlock.log")
+    });
     let file = OpenOptions::new()
         .create(true)
         .append(true)
@@ -19,6 +28,13 @@ static LOG_FILE: Lazy<Mutex<std::fs::File>> = Lazy::new(|| {
     Mutex::new(file)
 });
 
+/// Writes the log to `path` instead of `~/.sherlock/sherlock.log`, which keeps tests out of
+/// the user's log. Has no effect once the first message was logged.
+#[cfg(test)]
+pub fn set_log_path(path: PathBuf) {
+    let _ = LOG_PATH.set(path);
+}
+
 pub fn write_log<T: AsRef<str>>(message: T, file: &str, line: u32) {
     let message = message.as_ref();
     let now = Local::now().format("%Y-%m-%d %H:%M:%S");
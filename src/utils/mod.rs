@@ -1,4 +1,12 @@
+pub mod cli;
+#[cfg(test)]
+mod cli_assets;
 pub mod config;
 pub mod errors;
 pub mod files;
+pub mod fuzzy;
 pub mod logging;
+#[cfg(test)]
+pub mod test_api;
+#[cfg(test)]
+pub mod test_bus;
@@ -0,0 +1,62 @@
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    api::{
+        call::{ApiCall, ApiRequest, ApiResponse},
+        server::{ApiHandler, SherlockServer},
+    },
+    daemon::daemon::DaemonMessage,
+    utils::logging::set_log_path,
+};
+
+/// An [`ApiHandler`] that records calls instead of driving a GTK window
+#[derive(Default)]
+pub struct MockApi {
+    pub handled: Vec<String>,
+    pub queued: Vec<String>,
+}
+impl MockApi {
+    /// Answers the messages from `pipeline` through [`SherlockServer::dispatch`] like the api
+    /// would. Requests are logged to a temporary file instead of the user's log.
+    pub fn answer(api: Arc<Mutex<Self>>, pipeline: async_channel::Receiver<DaemonMessage>) {
+        set_log_path(std::env::temp_dir().join("sherlock-test.log"));
+        tokio::spawn(async move {
+            while let Ok(DaemonMessage { content, reply }) = pipeline.recv().await {
+                let response = SherlockServer::dispatch(&mut *api.lock().unwrap(), &content);
+                if let Some(response) = response {
+                    let _ = reply.send(serde_json::to_string(&response).unwrap()).await;
+                }
+            }
+        });
+    }
+}
+impl ApiHandler for MockApi {
+    fn handle_request(&mut self, request: ApiRequest) -> ApiResponse {
+        self.handled.push(request.call.to_string());
+        match request.call {
+            // Like the real api before the window is built
+            ApiCall::GetMode => {
+                ApiResponse::failure(request.id, format!("{} is not available yet", request.call))
+            }
+            ApiCall::GetQuery => ApiResponse::success(request.id, Some(json!("sus"))),
+            ApiCall::GetSelected => ApiResponse::success(
+                request.id,
+                Some(json!({"index": 1, "row": {
+                    "title": "Firefox",
+                    "launcher": "App Launcher",
+                    "priority": 1.0,
+                    "visible": true,
+                }})),
+            ),
+            _ => ApiResponse::success(request.id, None),
+        }
+    }
+    fn await_request(&mut self, request: ApiCall) -> Option<()> {
+        self.queued.push(request.to_string());
+        Some(())
+    }
+    fn flush(&mut self) -> Option<()> {
+        Some(())
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    env, fs,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+};
+
+const BUS_CONFIG: &str = r#"<!DOCTYPE busconfig PUBLIC "-//freedesktop//DTD D-Bus Bus Configuration 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/busconfig.dtd">
+<busconfig>
+  <type>session</type>
+  <listen>unix:tmpdir=/tmp</listen>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>
+"#;
+
+/// A private `dbus-daemon` used to test D-Bus clients and services in isolation
+pub struct TestBus {
+    daemon: Child,
+    config: PathBuf,
+    address: String,
+}
+impl TestBus {
+    /// Starts a new bus. Returns `None` if `dbus-daemon` is not installed.
+    pub fn start() -> Option<Self> {
+        let config = env::temp_dir().join(format!(
+            "sherlock-test-bus-{}-{:?}.conf",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        fs::write(&config, BUS_CONFIG).ok()?;
+        let mut daemon = Command::new("dbus-daemon")
+            .arg(format!("--config-file={}", config.display()))
+            .args(["--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        let stdout = daemon.stdout.take()?;
+        BufReader::new(stdout).read_line(&mut address).ok()?;
+        if address.trim().is_empty() {
+            let _ = daemon.kill();
+            return None;
+        }
+        Some(Self {
+            daemon,
+            config,
+            address: address.trim().to_string(),
+        })
+    }
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+}
+impl Drop for TestBus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+        let _ = fs::remove_file(&self.config);
+    }
+}